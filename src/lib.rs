@@ -1,10 +1,19 @@
-use core::hash::Hash;
-use std::collections::{HashMap, HashSet};
+#![allow(clippy::needless_return)]
+
+use core::hash::{Hash, Hasher};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet, VecDeque};
 
 #[derive(Debug)]
 pub enum DependencyError {
     SelfReference,
     CircularDependency,
+    DepthExceeded,
+    NotATree,
+    InvalidEdge {
+        index: usize,
+        error: Box<DependencyError>,
+    },
 }
 
 impl std::fmt::Display for DependencyError {
@@ -12,27 +21,304 @@ impl std::fmt::Display for DependencyError {
         match self {
             DependencyError::SelfReference => write!(f, "Self reference"),
             DependencyError::CircularDependency => write!(f, "Circular dependency"),
+            DependencyError::DepthExceeded => write!(f, "Depth exceeded"),
+            DependencyError::NotATree => write!(f, "Not a tree"),
+            DependencyError::InvalidEdge { index, error } => {
+                write!(f, "Invalid edge at index {}: {}", index, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DependencyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DependencyError::InvalidEdge { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// An error encountered while decoding a graph from bytes.
+/// # Variants
+/// * `InvalidHeader` - The data does not start with the expected format marker and version.
+/// * `UnexpectedEnd` - The data ends in the middle of a value.
+/// * `InvalidVarint` - A variable-length integer does not fit in 64 bits.
+/// * `InvalidNode` - A node key does not fit in the node type or repeats the previous key, or a node index is out of range.
+/// * `InvalidEdge` - A dependency could not be added to the graph.
+/// * `DuplicateEdge` - A dependency appears more than once.
+/// * `TrailingBytes` - There is data left after the graph.
+#[derive(Debug)]
+pub enum DecodeError {
+    InvalidHeader,
+    UnexpectedEnd,
+    InvalidVarint,
+    InvalidNode,
+    InvalidEdge(DependencyError),
+    DuplicateEdge,
+    TrailingBytes,
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DecodeError::InvalidHeader => write!(f, "Invalid header"),
+            DecodeError::UnexpectedEnd => write!(f, "Unexpected end of data"),
+            DecodeError::InvalidVarint => write!(f, "Invalid varint"),
+            DecodeError::InvalidNode => write!(f, "Invalid node"),
+            DecodeError::InvalidEdge(error) => write!(f, "Invalid edge: {}", error),
+            DecodeError::DuplicateEdge => write!(f, "Duplicate edge"),
+            DecodeError::TrailingBytes => write!(f, "Trailing bytes"),
         }
     }
 }
 
-impl std::error::Error for DependencyError {}
+impl std::error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DecodeError::InvalidEdge(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Aggregate statistics of the direct dependency counts of the nodes in a graph.
+/// # Fields
+/// * `min_out_degree` - The fewest direct dependencies of any node.
+/// * `max_out_degree` - The most direct dependencies of any node.
+/// * `mean_out_degree` - The average number of direct dependencies per node.
+/// * `max_out_degree_node` - A node with `max_out_degree` direct dependencies.
+/// * `min_in_degree` - The fewest direct dependents of any node.
+/// * `max_in_degree` - The most direct dependents of any node.
+/// * `mean_in_degree` - The average number of direct dependents per node.
+/// * `max_in_degree_node` - A node with `max_in_degree` direct dependents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DegreeStats<T> {
+    pub min_out_degree: usize,
+    pub max_out_degree: usize,
+    pub mean_out_degree: f64,
+    pub max_out_degree_node: Option<T>,
+    pub min_in_degree: usize,
+    pub max_in_degree: usize,
+    pub mean_in_degree: f64,
+    pub max_in_degree_node: Option<T>,
+}
+
+/// A single change to the direct dependencies of a graph.
+/// Produced by `AcyclicDependencyGraph::edge_patch_to()` and replayed by `AcyclicDependencyGraph::apply_edge_patch()`.
+/// # Variants
+/// * `Add(from, to)` - Add the direct dependency of `from` on `to`.
+/// * `Remove(from, to)` - Remove the direct dependency of `from` on `to`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum EdgeOp<T> {
+    Add(T, T),
+    Remove(T, T),
+}
 
 /// A map of direct dependencies.
 /// For a given node the value is the set of direct dependencies of that node.
 type DirectDependencyMap<T> = HashMap<T, HashSet<T>>;
 
-/// Remove a node from a dependency map.
-/// This removes the node from the map and removes the node from the dependency sets of all other nodes.
+/// Walk a dependency map breadth-first from a node.
+/// # Arguments
+/// * `map` - The dependency map to walk.
+/// * `start` - The node to start from.
+/// # Returns
+/// Each node reachable from `start`, excluding `start` itself, paired with its minimum distance from `start`, in breadth-first order.
+fn dependency_map_walk_with_depth<T: Eq + Hash + Clone>(
+    map: &DirectDependencyMap<T>,
+    start: &T,
+) -> Vec<(T, usize)> {
+    let mut visited = HashSet::from([start]);
+    let mut walk = Vec::new();
+
+    let mut discovered = vec![start];
+    let mut depth = 0;
+    while !discovered.is_empty() {
+        depth += 1;
+        let mut discoveries = Vec::new();
+        for node in discovered {
+            for neighbor in map.get(node).into_iter().flatten() {
+                if visited.insert(neighbor) {
+                    walk.push((neighbor.clone(), depth));
+                    discoveries.push(neighbor);
+                }
+            }
+        }
+        discovered = discoveries;
+    }
+    return walk;
+}
+
+/// A unit-capacity style flow network used for cut and matching computations.
+/// Edges are stored in pairs so that edge `e ^ 1` is the residual of edge `e`.
+struct FlowNetwork {
+    adjacency: Vec<Vec<usize>>,
+    targets: Vec<usize>,
+    capacities: Vec<usize>,
+}
+
+impl FlowNetwork {
+    /// Create a flow network with `size` vertices and no edges.
+    fn new(size: usize) -> Self {
+        FlowNetwork {
+            adjacency: vec![Vec::new(); size],
+            targets: Vec::new(),
+            capacities: Vec::new(),
+        }
+    }
+
+    /// Add a directed edge with the given capacity.
+    fn add_edge(&mut self, from: usize, to: usize, capacity: usize) {
+        self.adjacency[from].push(self.targets.len());
+        self.targets.push(to);
+        self.capacities.push(capacity);
+        self.adjacency[to].push(self.targets.len());
+        self.targets.push(from);
+        self.capacities.push(0);
+    }
+
+    /// Find the vertices reachable from `source` through edges with remaining capacity.
+    /// # Returns
+    /// For each vertex the edge used to discover it, `source` maps to `Some(usize::MAX)`.
+    fn residual_search(&self, source: usize) -> Vec<Option<usize>> {
+        let mut via = vec![None; self.adjacency.len()];
+        via[source] = Some(usize::MAX);
+        let mut queue = VecDeque::from([source]);
+        while let Some(vertex) = queue.pop_front() {
+            for &edge in &self.adjacency[vertex] {
+                let target = self.targets[edge];
+                if self.capacities[edge] > 0 && via[target].is_none() {
+                    via[target] = Some(edge);
+                    queue.push_back(target);
+                }
+            }
+        }
+        return via;
+    }
+
+    /// Compute the maximum flow from `source` to `sink` (Edmonds-Karp).
+    /// # Remarks
+    /// Capacities are consumed, leaving the residual network for cut queries.
+    fn max_flow(&mut self, source: usize, sink: usize) -> usize {
+        let mut flow = 0;
+        loop {
+            let via = self.residual_search(source);
+            if via[sink].is_none() {
+                break;
+            }
+
+            // find the bottleneck along the augmenting path then push flow through it
+            let mut bottleneck = usize::MAX;
+            let mut vertex = sink;
+            while vertex != source {
+                let edge = via[vertex].unwrap();
+                bottleneck = bottleneck.min(self.capacities[edge]);
+                vertex = self.targets[edge ^ 1];
+            }
+            let mut vertex = sink;
+            while vertex != source {
+                let edge = via[vertex].unwrap();
+                self.capacities[edge] -= bottleneck;
+                self.capacities[edge ^ 1] += bottleneck;
+                vertex = self.targets[edge ^ 1];
+            }
+            flow += bottleneck;
+        }
+        return flow;
+    }
+}
+
+/// Escape a value for use as a CSV field.
+/// Values containing commas, quotes or line breaks are quoted with inner quotes doubled.
+fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        return format!("\"{}\"", value.replace('"', "\"\""));
+    }
+    return value.to_string();
+}
+
+/// Advance a splitmix64 generator and return its next value.
+/// # Arguments
+/// * `state` - The generator state, updated in place.
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    return z ^ (z >> 31);
+}
+
+/// Quote a value as a Mermaid node label.
+/// # Arguments
+/// * `value` - The label text.
+/// # Returns
+/// The value wrapped in double quotes, with `#` and quotes replaced by Mermaid entity codes.
+fn mermaid_label(value: &str) -> String {
+    return format!("\"{}\"", value.replace('#', "#35;").replace('"', "#quot;"));
+}
+
+/// Quote a string as a JSON string literal.
+/// # Arguments
+/// * `value` - The string to quote.
+/// # Returns
+/// The value wrapped in double quotes, with quotes, backslashes and control characters escaped.
+fn json_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    return quoted;
+}
+
+/// The leading bytes of the binary format written by `AcyclicDependencyGraph::to_bytes()`.
+const BINARY_MAGIC: [u8; 4] = *b"TPLG";
+
+/// The version of the binary format written by `AcyclicDependencyGraph::to_bytes()`.
+const BINARY_VERSION: u8 = 1;
+/// Append an unsigned LEB128 varint.
 /// # Arguments
-/// * `map` - The dependency map to remove the node from.
-/// * `node` - The node to remove from the dependency map.
-fn dependency_map_remove_node<T: Eq + Hash>(map: &mut DirectDependencyMap<T>, node: &T) {
-    map.remove(node);
-    for (_, deps) in &mut *map {
-        deps.remove(&node);
-    }
-    map.retain(|_, deps| deps.len() > 0);
+/// * `bytes` - The buffer to append to.
+/// * `value` - The value to encode.
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+/// Read an unsigned LEB128 varint.
+/// # Arguments
+/// * `data` - The buffer to read from.
+/// * `offset` - The position to read at, advanced past the varint.
+/// # Returns
+/// The decoded value, or an error if the buffer ends early or the value does not fit in a `u64`.
+fn read_varint(data: &[u8], offset: &mut usize) -> Result<u64, DecodeError> {
+    let mut value: u64 = 0;
+    let mut shift = 0;
+    loop {
+        let byte = *data.get(*offset).ok_or(DecodeError::UnexpectedEnd)?;
+        *offset += 1;
+        if (shift == 63 && byte > 1) || shift > 63 {
+            return Err(DecodeError::InvalidVarint);
+        }
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+    }
 }
 
 /// A directed acyclic graph of dependencies.
@@ -42,21 +328,124 @@ fn dependency_map_remove_node<T: Eq + Hash>(map: &mut DirectDependencyMap<T>, no
 /// * `nodes` - The set of nodes in the graph.
 /// * `forward_dependencies` - A map of direct dependencies.
 /// * `backward_dependencies` - A map of direct dependents.
+/// * `duplicate_edge_inserts` - The number of times an existing dependency was added again.
 /// # Methods
 /// * `new()` - Create a new empty graph.
+/// * `with_capacity()` - Create a new empty graph with room for a number of nodes.
+/// * `clear()` - Remove all nodes and dependencies.
 /// * `depend_on()` - Add a dependency between two nodes.
+/// * `load_edges_with_progress()` - Add many dependencies while reporting progress.
 /// * `depends_on()` - Check if one node depends on another.
 /// * `get_forward_dependencies()` - Get the set of nodes that a given node depends on.
+/// * `collect_forward_dependencies_into()` - Collect the set of nodes that a given node depends on into a caller-provided set.
 /// * `get_backward_dependencies()` - Get the set of nodes that depend on a given node.
 /// * `get_leaves()` - Get the set of nodes that have no dependencies.
 /// * `get_roots()` - Get the set of nodes that have no dependents.
 /// * `get_forward_dependency_topological_layers()` - Get the topological layers of the graph in forward direction.
 /// * `get_backward_dependency_topological_layers()` - Get the topological layers of the graph in backward direction.
+/// * `min_vertex_cut()` - Get a smallest set of nodes whose removal separates two nodes.
+/// * `is_isomorphic_to()` - Check if two graphs have the same shape regardless of node identity.
+/// * `transitive_closure()` - Get a graph with a direct dependency for every transitive dependency.
+/// * `find_self_references()` - Find every self referencing pair in a list of dependencies.
+/// * `is_weakly_connected()` - Check if the graph forms a single connected unit.
+/// * `layers_with_long_edges()` - Get the forward topological layers along with the dependencies that skip layers.
+/// * `degree_stats()` - Get aggregate statistics of the direct dependency counts.
+/// * `direct_dependents()` - Iterate over the nodes that directly depend on a given node.
+/// * `articulation_points()` - Get the nodes whose removal would fragment the graph.
+/// * `is_on_path()` - Check if a node lies on some path between two nodes.
+/// * `to_csv()` - Export the direct dependencies as CSV.
+/// * `nodes_not_reaching_any_leaf()` - Get the nodes that do not depend on any leaf.
+/// * `min_path_cover()` - Get the fewest dependency chains needed to cover every node.
+/// * `reverse_topological_iter()` - Iterate over the nodes so that every node comes before its dependencies.
+/// * `is_subgraph_of()` - Check if every node and direct dependency of this graph exists in another graph.
+/// * `get_forward_dependencies_cow()` - Get the set of nodes that a given node depends on, borrowing when possible.
+/// * `longest_edge()` - Get the direct dependency that skips the most topological layers.
+/// * `dependency_entry()` - Get an entry for conditionally modifying the direct dependencies of a node.
+/// * `forward_walk_with_depth()` - Get the nodes reachable from a given node along with their distance from it.
+/// * `is_valid_frontier()` - Check if a set of nodes contains all of its own dependencies.
+/// * `nodes_by_descendant_count()` - Get all nodes ranked by how many nodes they depend on.
+/// * `structural_fingerprint()` - Get a hash of the nodes and direct dependencies of the graph.
+/// * `schedule_batches()` - Get the forward topological layers as deterministically ordered batches.
+/// * `orphans_if_removed()` - Get the nodes that would no longer be reachable from any root if a node were removed.
+/// * `dependencies_by_root()` - Get the set of nodes that each root depends on.
+/// * `minimal_leaf_requirements()` - Get the leaves that must be provided to satisfy a node.
+/// * `edge_betweenness()` - Get how many shortest paths between nodes pass through each direct dependency.
+/// * `has_unique_topological_order()` - Check if the dependencies fully determine the processing order.
+/// * `retain_edges()` - Keep only the direct dependencies matching a predicate.
+/// * `condense()` - Collapse groups of nodes into single nodes.
+/// * `depend_on_tracked()` - Add a dependency between two nodes and report the newly reachable dependencies.
+/// * `edges_topologically()` - Get the direct dependencies ordered so that every dependency on a node comes before the dependencies of that node.
+/// * `blast_radius()` - Get the nodes affected by a change to some nodes.
+/// * `nodes_at_forward_depth()` - Get a single forward topological layer by index.
+/// * `duplicate_edge_insert_count()` - Get the number of times an existing dependency was added again.
+/// * `ancestors_by_depth()` - Get the nodes that depend on a given node along with their distance from it.
+/// * `structural_twins()` - Get the pairs of nodes with identical direct dependencies and dependents.
+/// * `irreversible_edges()` - Get the direct dependencies whose reversal would create a circular dependency.
+/// * `random_topological_order()` - Get a pseudo-random order in which every node comes after its dependencies.
+/// * `transitive_dependent_count()` - Get the number of nodes that depend on a given node.
+/// * `nodes_by_dependent_count()` - Get all nodes ranked by how many nodes depend on them.
+/// * `is_valid_topological_order()` - Check if an order of the nodes puts every dependency before its dependents.
+/// * `shared_between()` - Get the nodes needed by both of two nodes.
+/// * `to_mermaid()` - Export the direct dependencies as a Mermaid flowchart.
+/// * `average_depth()` - Get the mean forward topological layer index of the nodes.
+/// * `longest_path()` - Get a longest chain of direct dependencies in the graph.
+/// * `build_reachability_index()` - Precompute the dependencies of every node for constant time queries.
+/// * `incomparable_pairs()` - Get the pairs of nodes where neither depends on the other.
+/// * `is_edge_redundant()` - Check if a direct dependency is implied by other dependencies.
+/// * `subtree_forward_layers()` - Get the forward topological layers of a node and its dependencies.
+/// * `divergence_point()` - Get the node where the shortest paths to two nodes split.
+/// * `shrink_to_fit()` - Release excess memory held by the graph.
+/// * `merge_mapped()` - Combine several graphs under a common node type.
+/// * `is_leaf()` - Check if a node has no dependencies.
+/// * `is_root()` - Check if a node has no dependents.
+/// * `layer_widths()` - Get the number of nodes in each forward topological layer.
+/// * `earliest_layer_with_floor()` - Get the earliest layer a node can be scheduled in given minimum layers for some nodes.
+/// * `high_degree_nodes()` - Get the nodes with more direct dependencies and dependents than a threshold.
+/// * `transitive_reduction_with_removed()` - Get the transitive reduction of the graph along with the dependencies it drops.
+/// * `subtrees_disjoint()` - Check if two nodes share no dependencies.
+/// * `slack()` - Get how many layers each node can be delayed without lengthening the schedule.
+/// * `add_edge_and_update_layers()` - Add a dependency and patch a forward layering to match.
+/// * `to_json()` - Export the direct dependencies as a JSON adjacency list.
+/// * `unlock_counts()` - Get how many dependents each leaf would make ready on its own.
+/// * `case_insensitive_collisions()` - Get pairs of nodes whose names differ only by case.
+/// * `width()` - Get the size of the largest set of mutually independent nodes.
+/// * `ordered_layers()` - Get the forward topological layers ordered to reduce edge crossings.
+/// * `can_add_all()` - Check whether a batch of dependencies could be added without modifying the graph.
+/// * `dominators()` - Get the immediate dominator of every node reachable from a root.
+/// * `diamond_edges()` - Get the direct dependencies that are part of a diamond.
+/// * `stable_labeling()` - Assign each node a reproducible integer index.
+/// * `is_independent_set()` - Check that no direct dependency joins two nodes of a set.
+/// * `grouped_by_dependent()` - Get each node's sorted direct dependencies, prerequisites first.
+/// * `minimal_covering_roots()` - Get the fewest nodes whose transitive dependencies cover the graph.
+/// * `path_centrality()` - Count the root-to-leaf paths through each node.
+/// * `backward_depths_from_forward()` - Derive backward layer indices from forward ones.
+/// * `near_cyclic_edges()` - Get the direct dependencies that would close a short cycle if reversed.
+/// * `to_bytes()` - Encode a graph with integer nodes in a compact binary format.
+/// * `from_bytes()` - Decode a graph written by `to_bytes()`.
+/// * `split_components()` - Split the graph into one graph per weakly connected component.
+/// * `edge_patch_to()` - Get the dependency additions and removals that turn this graph's edges into another's.
+/// * `apply_edge_patch()` - Replay dependency additions and removals.
+/// * `depend_on_limited()` - Add a dependency unless it would make the graph too deep.
+/// * `not_required_by()` - Get the nodes that a node neither is nor depends on.
+/// * `node_betweenness()` - Get how many shortest paths between other nodes pass through each node.
+/// * `topological_sort_by()` - Get an order in which every node comes after its dependencies, preferring nodes by a comparator.
+/// * `repair()` - Remove dependencies that refer to missing nodes or are not mirrored.
+/// * `schedule_with_workers()` - Assign the nodes to time slots for a fixed number of parallel workers.
+/// * `alap_layers()` - Get the latest forward layer each node can occupy without adding layers.
+/// * `longest_path_edges()` - Get the direct dependencies along a longest chain.
+/// * `depend_on_tree()` - Add a dependency only if the depending node has no other dependency.
+/// * `remove_edge_tracked()` - Remove a dependency and get the transitive dependencies lost by it.
+/// * `passthrough_nodes()` - Get the nodes with exactly one dependency and one dependent.
+/// * `out_degree_histogram()` - Count the nodes with each number of direct dependencies.
+/// * `has_exactly_edges()` - Check that the direct dependencies are exactly a given set.
+/// * `impact_score()` - Combine a node's depth and dependent count into a single weighted score.
+/// * `build_from_lines()` - Build a graph from numbered dependency lines, reporting the first bad line.
 #[derive(Clone)]
 pub struct AcyclicDependencyGraph<T> {
     nodes: HashSet<T>,
     forward_dependencies: DirectDependencyMap<T>,
     backward_dependencies: DirectDependencyMap<T>,
+    duplicate_edge_inserts: usize,
 }
 
 impl<T> AcyclicDependencyGraph<T>
@@ -71,6 +460,23 @@ where
             nodes: HashSet::new(),
             forward_dependencies: HashMap::new(),
             backward_dependencies: HashMap::new(),
+            duplicate_edge_inserts: 0,
+        }
+    }
+
+    /// Create a new empty graph with room for a number of nodes.
+    /// # Arguments
+    /// * `nodes` - The number of nodes to preallocate space for.
+    /// # Returns
+    /// A new empty graph.
+    /// # Remarks
+    /// Avoids rehashing while loading a graph of known size.
+    pub fn with_capacity(nodes: usize) -> Self {
+        AcyclicDependencyGraph {
+            nodes: HashSet::with_capacity(nodes),
+            forward_dependencies: HashMap::with_capacity(nodes),
+            backward_dependencies: HashMap::with_capacity(nodes),
+            duplicate_edge_inserts: 0,
         }
     }
 
@@ -82,15 +488,56 @@ where
         self.nodes.is_empty()
     }
 
-    /// Remove a node from the graph.
+    /// Remove all nodes and dependencies.
+    /// # Remarks
+    /// Also resets `duplicate_edge_insert_count()`.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.forward_dependencies.clear();
+        self.backward_dependencies.clear();
+        self.duplicate_edge_inserts = 0;
+    }
+
+    /// Verify that the forward and backward dependency maps describe the same edges.
+    /// # Panics
+    /// If an edge is missing from either map, an endpoint is not a node of the graph, or a dependency set is empty.
+    #[cfg(any(test, debug_assertions))]
+    fn assert_consistent(&self) {
+        for map in [&self.forward_dependencies, &self.backward_dependencies] {
+            for node in map.keys() {
+                assert!(self.nodes.contains(node), "edge endpoint is not a node");
+            }
+        }
+        for node in &self.nodes {
+            self.assert_consistent_at(node);
+        }
+    }
+
+    /// Verify that the dependencies and dependents of a single node are mirrored in the other map.
     /// # Arguments
-    /// * `node` - The node to remove from the graph.
+    /// * `node` - The node to check.
+    /// # Panics
+    /// If an edge of the node is missing from either map, an endpoint is not a node of the graph, or a dependency set is empty.
     /// # Remarks
-    /// This removes the node from the graph and removes the node from the dependency sets of all other nodes.
-    fn remove_node(&mut self, node: T) {
-        self.nodes.remove(&node);
-        dependency_map_remove_node(&mut self.forward_dependencies, &node);
-        dependency_map_remove_node(&mut self.backward_dependencies, &node);
+    /// Takes time proportional to the degree of the node, so it is cheap enough to run after every single edge change.
+    #[cfg(any(test, debug_assertions))]
+    fn assert_consistent_at(&self, node: &T) {
+        let maps = [
+            (&self.forward_dependencies, &self.backward_dependencies),
+            (&self.backward_dependencies, &self.forward_dependencies),
+        ];
+        for (map, mirror) in maps {
+            if let Some(deps) = map.get(node) {
+                assert!(!deps.is_empty(), "empty dependency set");
+                for dep in deps {
+                    assert!(self.nodes.contains(dep), "edge endpoint is not a node");
+                    assert!(
+                        mirror.get(dep).is_some_and(|deps| deps.contains(node)),
+                        "dependency maps out of sync"
+                    );
+                }
+            }
+        }
     }
 
     /// Get the set of nodes that have no dependencies.
@@ -99,7 +546,7 @@ where
     pub fn get_leaves(&self) -> HashSet<T> {
         let mut leaves: HashSet<T> = HashSet::new();
         for node in &self.nodes {
-            if self.forward_dependencies.get(&node).is_none() {
+            if !self.forward_dependencies.contains_key(node) {
                 leaves.insert(node.clone());
             }
         }
@@ -112,7 +559,7 @@ where
     pub fn get_roots(&self) -> HashSet<T> {
         let mut roots: HashSet<T> = HashSet::new();
         for node in &self.nodes {
-            if self.backward_dependencies.get(&node).is_none() {
+            if !self.backward_dependencies.contains_key(node) {
                 roots.insert(node.clone());
             }
         }
@@ -136,6 +583,13 @@ where
         if self.depends_on(&to, &from) {
             return Err(DependencyError::CircularDependency);
         }
+        if self.contains_edge(&from, &to) {
+            self.duplicate_edge_inserts += 1;
+            return Ok(());
+        }
+
+        #[cfg(debug_assertions)]
+        let touched = [from.clone(), to.clone()];
 
         // ensure that nodes are accounted for in the graph
         self.nodes.insert(from.clone());
@@ -144,13 +598,49 @@ where
         // add the forward and backward dependency edges
         self.forward_dependencies
             .entry(from.clone())
-            .or_insert(HashSet::new())
+            .or_default()
             .insert(to.clone());
         self.backward_dependencies
             .entry(to)
-            .or_insert(HashSet::new())
+            .or_default()
             .insert(from);
 
+        #[cfg(debug_assertions)]
+        for node in &touched {
+            self.assert_consistent_at(node);
+        }
+
+        return Ok(());
+    }
+
+    /// Add many dependencies while reporting progress.
+    /// # Arguments
+    /// * `edges` - The `(from, to)` dependency pairs to add, in order.
+    /// * `progress` - Called as `progress(done, total)` after every 1% of the edges and once at the end.
+    /// # Returns
+    /// `Ok(())` if all dependencies were added successfully.
+    /// `Err(DependencyError::InvalidEdge { index, error })` for the first edge that could not be added.
+    /// # Remarks
+    /// Edges before the failing edge remain in the graph.
+    pub fn load_edges_with_progress<F: FnMut(usize, usize)>(
+        &mut self,
+        edges: &[(T, T)],
+        mut progress: F,
+    ) -> Result<(), DependencyError> {
+        let total = edges.len();
+        let interval = usize::max(total / 100, 1);
+        for (index, (from, to)) in edges.iter().enumerate() {
+            if let Err(error) = self.depend_on(from.clone(), to.clone()) {
+                return Err(DependencyError::InvalidEdge {
+                    index,
+                    error: Box::new(error),
+                });
+            }
+            let done = index + 1;
+            if done % interval == 0 || done == total {
+                progress(done, total);
+            }
+        }
         return Ok(());
     }
 
@@ -172,13 +662,25 @@ where
     /// The set of nodes that the given node depends on.
     pub fn get_forward_dependencies(&self, node: &T) -> HashSet<T> {
         let mut out = HashSet::new();
+        self.collect_forward_dependencies_into(node.clone(), &mut out);
+        return out;
+    }
 
-        let mut discovered = vec![node];
-        while discovered.len() > 0 {
+    /// Collect the set of nodes that a given node depends on into a caller-provided set.
+    /// # Arguments
+    /// * `node` - The node to get the dependencies of.
+    /// * `out` - The set to fill. It is cleared first.
+    /// # Remarks
+    /// Reusing `out` across calls avoids allocating a new set for every query.
+    pub fn collect_forward_dependencies_into(&self, node: T, out: &mut HashSet<T>) {
+        out.clear();
+
+        let mut discovered = vec![&node];
+        while !discovered.is_empty() {
             let mut discoveries = Vec::new();
             for node in discovered {
                 // get direct dependencies of the given node
-                let direct_dependencies: &HashSet<T> = match self.forward_dependencies.get(&node) {
+                let direct_dependencies: &HashSet<T> = match self.forward_dependencies.get(node) {
                     Some(deps) => deps,
                     None => continue,
                 };
@@ -193,8 +695,6 @@ where
             }
             discovered = discoveries;
         }
-
-        return out;
     }
 
     /// Get the set of nodes that depend on a given node.
@@ -206,11 +706,11 @@ where
         let mut out = HashSet::new();
 
         let mut discovered = vec![node];
-        while discovered.len() > 0 {
+        while !discovered.is_empty() {
             let mut discoveries = Vec::new();
             for node in discovered {
                 // get direct dependencies of the given node
-                let direct_dependencies: &HashSet<T> = match self.backward_dependencies.get(&node) {
+                let direct_dependencies: &HashSet<T> = match self.backward_dependencies.get(node) {
                     Some(deps) => deps,
                     None => continue,
                 };
@@ -240,19 +740,7 @@ where
     /// The particular ordering of topological layers is not guaranteed.
     /// The only guarantee is that the nodes in each layer depend only on the nodes in the previous layers.
     pub fn get_forward_dependency_topological_layers(&self) -> Vec<HashSet<T>> {
-        let mut layers = Vec::new();
-        let mut shrinking_graph = self.clone();
-        loop {
-            let leaves = shrinking_graph.get_leaves();
-            if leaves.len() == 0 {
-                break;
-            }
-            for leaf in &leaves {
-                shrinking_graph.remove_node(leaf.clone());
-            }
-            layers.push(leaves);
-        }
-        return layers;
+        return self.peel_layers(&self.forward_dependencies, &self.backward_dependencies);
     }
 
     /// Get the topological layers of the graph in backward direction.
@@ -266,114 +754,5048 @@ where
     /// The particular ordering of topological layers is not guaranteed.
     /// The only guarantee is that the nodes in each layer are depended on only by the nodes in the previous layers.
     pub fn get_backward_dependency_topological_layers(&self) -> Vec<HashSet<T>> {
+        return self.peel_layers(&self.backward_dependencies, &self.forward_dependencies);
+    }
+
+    /// Split the nodes into topological layers without modifying the graph.
+    /// # Arguments
+    /// * `outgoing` - The map of links which must all lead to earlier layers.
+    /// * `incoming` - The mirror of `outgoing`.
+    /// # Returns
+    /// The layers, where the first layer holds the nodes without `outgoing` links.
+    /// # Remarks
+    /// Counts the remaining `outgoing` links of each node instead of removing nodes from a copy of the graph, taking O(V + E) time.
+    fn peel_layers(
+        &self,
+        outgoing: &DirectDependencyMap<T>,
+        incoming: &DirectDependencyMap<T>,
+    ) -> Vec<HashSet<T>> {
+        let mut remaining: HashMap<&T, usize> = outgoing
+            .iter()
+            .map(|(node, links)| (node, links.len()))
+            .collect();
+        let mut layer: Vec<&T> = self
+            .nodes
+            .iter()
+            .filter(|node| !remaining.contains_key(node))
+            .collect();
+
         let mut layers = Vec::new();
-        let mut shrinking_graph = self.clone();
-        loop {
-            let roots = shrinking_graph.get_roots();
-            if roots.len() == 0 {
-                break;
-            }
-            for root in &roots {
-                shrinking_graph.remove_node(root.clone());
+        while !layer.is_empty() {
+            let mut next = Vec::new();
+            for node in &layer {
+                for neighbor in incoming.get(*node).into_iter().flatten() {
+                    let count = remaining.get_mut(neighbor).unwrap();
+                    *count -= 1;
+                    if *count == 0 {
+                        next.push(neighbor);
+                    }
+                }
             }
-            layers.push(roots);
+            layers.push(layer.into_iter().cloned().collect());
+            layer = next;
         }
         return layers;
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Get a smallest set of intermediate nodes whose removal severs all paths between two nodes.
+    /// # Arguments
+    /// * `from` - The node that depends on the other node.
+    /// * `to` - The node that is depended on.
+    /// # Returns
+    /// A minimum set of nodes, excluding `from` and `to`, such that no forward path from `from` to `to` remains once they are removed.
+    /// The set is empty if `from` does not depend on `to`.
+    /// # Remarks
+    /// Computed as a max-flow/min-cut on the node-split graph, taking O(k * (V + E)) time for a cut of size k.
+    /// A direct `from -> to` dependency cannot be severed by removing intermediate nodes and is ignored.
+    pub fn min_vertex_cut(&self, from: T, to: T) -> HashSet<T> {
+        let mut cut = HashSet::new();
+        let upstream = self.get_backward_dependencies(&to);
+        if !upstream.contains(&from) {
+            return cut;
+        }
 
-    #[test]
-    fn empty_graph_is_empty() {
-        let graph: AcyclicDependencyGraph<&str> = AcyclicDependencyGraph::new();
-        assert!(graph.is_empty());
+        // only nodes on some path from `from` to `to` can take part in the cut
+        let mut intermediates = self.get_forward_dependencies(&from);
+        intermediates.retain(|node| upstream.contains(node));
+        let mut nodes = vec![&from, &to];
+        nodes.extend(intermediates.iter());
+        let index: HashMap<&T, usize> = nodes.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+
+        // split every node into an `in` vertex (2i) and an `out` vertex (2i + 1)
+        let unbounded = nodes.len();
+        let mut network = FlowNetwork::new(2 * nodes.len());
+        for (i, node) in nodes.iter().enumerate() {
+            let capacity = if i < 2 { unbounded } else { 1 };
+            network.add_edge(2 * i, 2 * i + 1, capacity);
+            for dependency in self.forward_dependencies.get(*node).into_iter().flatten() {
+                match index.get(dependency) {
+                    Some(&j) if !(i == 0 && j == 1) => {
+                        network.add_edge(2 * i + 1, 2 * j, unbounded)
+                    }
+                    _ => continue,
+                }
+            }
+        }
+        network.max_flow(1, 2);
+
+        // the cut consists of the nodes whose `in` vertex is reachable but whose `out` vertex is not
+        let reachable = network.residual_search(1);
+        for (i, node) in nodes.iter().enumerate().skip(2) {
+            if reachable[2 * i].is_some() && reachable[2 * i + 1].is_none() {
+                cut.insert((*node).clone());
+            }
+        }
+        return cut;
     }
 
-    #[test]
-    fn non_empty_graph_is_not_empty() {
-        let mut graph = AcyclicDependencyGraph::new();
-        graph.depend_on("a", "b").unwrap();
-        assert!(!graph.is_empty());
+    /// Number the nodes of the graph and express the direct dependencies in terms of those numbers.
+    /// # Returns
+    /// The numbered nodes and, for each node number, the set of numbers of its direct dependencies.
+    fn indexed_forward_dependencies(&self) -> (Vec<&T>, Vec<HashSet<usize>>) {
+        let nodes: Vec<&T> = self.nodes.iter().collect();
+        let index: HashMap<&T, usize> = nodes.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+        let dependencies = nodes
+            .iter()
+            .map(|node| match self.forward_dependencies.get(*node) {
+                Some(deps) => deps.iter().map(|dep| index[dep]).collect(),
+                None => HashSet::new(),
+            })
+            .collect();
+        return (nodes, dependencies);
     }
 
-    #[test]
-    fn self_referential_dependencies_detected() {
-        let mut graph = AcyclicDependencyGraph::new();
-        assert!(graph.depend_on("a", "a").is_err());
+    /// Check if two graphs have the same shape regardless of node identity.
+    /// # Arguments
+    /// * `other` - The graph to compare against.
+    /// # Returns
+    /// `true` if there is a bijection between the nodes of the graphs which preserves every direct dependency.
+    /// `false` otherwise.
+    /// # Remarks
+    /// Graph isomorphism is expensive in general so this is intended for small graphs.
+    /// Candidate pairings are pruned by in- and out-degree before backtracking.
+    pub fn is_isomorphic_to<U>(&self, other: &AcyclicDependencyGraph<U>) -> bool
+    where
+        U: Eq + Hash + Clone,
+    {
+        let (_, ours) = self.indexed_forward_dependencies();
+        let (_, theirs) = other.indexed_forward_dependencies();
+        if ours.len() != theirs.len() {
+            return false;
+        }
+        let degrees = |deps: &Vec<HashSet<usize>>| {
+            let mut degrees = vec![(0, 0); deps.len()];
+            for (i, targets) in deps.iter().enumerate() {
+                degrees[i].0 = targets.len();
+                for &j in targets {
+                    degrees[j].1 += 1;
+                }
+            }
+            degrees
+        };
+        let our_degrees = degrees(&ours);
+        let their_degrees = degrees(&theirs);
+        let mut our_sequence = our_degrees.clone();
+        let mut their_sequence = their_degrees.clone();
+        our_sequence.sort();
+        their_sequence.sort();
+        if our_sequence != their_sequence {
+            return false;
+        }
+
+        // try to extend a partial mapping one node at a time, backtracking on conflicts
+        let count = ours.len();
+        let mut mapping: Vec<usize> = Vec::with_capacity(count);
+        let mut used = vec![false; count];
+        let mut next_candidate = 0;
+        while mapping.len() < count {
+            let i = mapping.len();
+            let candidate = (next_candidate..count).find(|&j| {
+                !used[j]
+                    && our_degrees[i] == their_degrees[j]
+                    && mapping.iter().enumerate().all(|(k, &m)| {
+                        ours[i].contains(&k) == theirs[j].contains(&m)
+                            && ours[k].contains(&i) == theirs[m].contains(&j)
+                    })
+            });
+            match candidate {
+                Some(j) => {
+                    used[j] = true;
+                    mapping.push(j);
+                    next_candidate = 0;
+                }
+                None => match mapping.pop() {
+                    Some(j) => {
+                        used[j] = false;
+                        next_candidate = j + 1;
+                    }
+                    None => return false,
+                },
+            }
+        }
+        return true;
     }
 
-    #[test]
-    fn circular_dependencies_detected() {
-        let mut graph = AcyclicDependencyGraph::new();
-        graph.depend_on("a", "b").unwrap();
-        graph.depend_on("b", "c").unwrap();
-        assert!(graph.depend_on("c", "a").is_err());
+    /// Get a graph with a direct dependency for every transitive dependency.
+    /// # Returns
+    /// A new graph with the same nodes in which `a` directly depends on `b` if and only if `a` depends on `b` in this graph.
+    /// # Remarks
+    /// This is the dual of a transitive reduction.
+    /// Querying direct dependencies of the closure answers reachability questions without traversal.
+    pub fn transitive_closure(&self) -> AcyclicDependencyGraph<T> {
+        let mut closure = AcyclicDependencyGraph::new();
+        closure.nodes = self.nodes.clone();
+        for node in self.forward_dependencies.keys() {
+            let dependencies = self.get_forward_dependencies(node);
+            for dependency in &dependencies {
+                closure
+                    .backward_dependencies
+                    .entry(dependency.clone())
+                    .or_default()
+                    .insert(node.clone());
+            }
+            closure
+                .forward_dependencies
+                .insert(node.clone(), dependencies);
+        }
+        return closure;
     }
 
-    #[test]
-    fn simple_topological_sort_forward() {
-        let mut graph = AcyclicDependencyGraph::new();
-        graph.depend_on("cake", "eggs").unwrap();
-        graph.depend_on("cake", "flour").unwrap();
-        graph.depend_on("eggs", "chickens").unwrap();
-        graph.depend_on("flour", "grain").unwrap();
-        graph.depend_on("chickens", "grain").unwrap();
-        graph.depend_on("grain", "soil").unwrap();
-        graph.depend_on("grain", "water").unwrap();
-        graph.depend_on("chickens", "water").unwrap();
+    /// Find every self referencing pair in a list of dependencies.
+    /// # Arguments
+    /// * `edges` - The `(from, to)` dependency pairs to check.
+    /// # Returns
+    /// Every pair where `from == to`, in the order they appear in `edges`.
+    /// # Remarks
+    /// Useful to report all invalid pairs at once rather than failing on the first one while loading.
+    pub fn find_self_references(edges: &[(T, T)]) -> Vec<(T, T)> {
+        return edges
+            .iter()
+            .filter(|(from, to)| from == to)
+            .cloned()
+            .collect();
+    }
 
-        let layers = graph.get_forward_dependency_topological_layers();
-        println!("layers: {:?}", layers);
+    /// Get the nodes connected to a given node when ignoring the direction of dependencies.
+    /// # Arguments
+    /// * `start` - The node to start from.
+    /// # Returns
+    /// The weakly connected component containing `start`, including `start` itself.
+    fn weakly_connected_component<'a>(&'a self, start: &'a T) -> HashSet<&'a T> {
+        let mut component = HashSet::from([start]);
+        let mut discovered = vec![start];
+        while let Some(node) = discovered.pop() {
+            let neighbors = self
+                .forward_dependencies
+                .get(node)
+                .into_iter()
+                .chain(self.backward_dependencies.get(node))
+                .flatten();
+            for neighbor in neighbors {
+                if component.insert(neighbor) {
+                    discovered.push(neighbor);
+                }
+            }
+        }
+        return component;
+    }
 
-        // check forward and backward direct dependencies
-        // note: we cannot guarantee any particular topological ordering so checking the direct dependencies is a second-best option
-        let fwd_bckwd_check = |node: &str, expected_fwd: Vec<&str>, expected_bwd: Vec<&str>| {
-            let fwd = graph.get_forward_dependencies(&node);
-            let bwd = graph.get_backward_dependencies(&node);
+    /// Check if the graph forms a single connected unit.
+    /// # Returns
+    /// `true` if every node is connected to every other node when ignoring the direction of dependencies, or if the graph is empty.
+    /// `false` if the graph is fragmented.
+    pub fn is_weakly_connected(&self) -> bool {
+        match self.nodes.iter().next() {
+            Some(node) => self.weakly_connected_component(node).len() == self.nodes.len(),
+            None => true,
+        }
+    }
 
-            assert_eq!(fwd.len(), expected_fwd.len());
-            assert_eq!(bwd.len(), expected_bwd.len());
-            for expected_node in expected_fwd {
-                assert!(fwd.contains(&expected_node));
+    /// Get the forward topological layers along with the dependencies that skip layers.
+    /// # Returns
+    /// The forward topological layers and every direct dependency `(from, to)` whose endpoints are not in adjacent layers.
+    /// # Remarks
+    /// Long dependencies need special routing in layered (Sugiyama style) drawings.
+    pub fn layers_with_long_edges(&self) -> (Vec<HashSet<T>>, Vec<(T, T)>) {
+        let layers = self.get_forward_dependency_topological_layers();
+        let mut depths = HashMap::new();
+        for (depth, layer) in layers.iter().enumerate() {
+            for node in layer {
+                depths.insert(node, depth);
             }
-            for expected_node in expected_bwd {
-                println!("expected_node: {}", &expected_node);
-                assert!(bwd.contains(&expected_node));
+        }
+
+        let mut long_edges = Vec::new();
+        for (from, deps) in &self.forward_dependencies {
+            for to in deps {
+                if depths[from] - depths[to] > 1 {
+                    long_edges.push((from.clone(), to.clone()));
+                }
             }
+        }
+        return (layers, long_edges);
+    }
+
+    /// Get aggregate statistics of the direct dependency counts.
+    /// # Returns
+    /// The minimum, maximum and mean number of direct dependencies (out-degree) and direct dependents (in-degree) across all nodes.
+    /// All values are zero and the nodes are `None` for an empty graph.
+    /// # Remarks
+    /// When several nodes share the maximum degree an arbitrary one of them is reported.
+    pub fn degree_stats(&self) -> DegreeStats<T> {
+        let mut stats = DegreeStats {
+            min_out_degree: 0,
+            max_out_degree: 0,
+            mean_out_degree: 0.0,
+            max_out_degree_node: None,
+            min_in_degree: 0,
+            max_in_degree: 0,
+            mean_in_degree: 0.0,
+            max_in_degree_node: None,
         };
-        fwd_bckwd_check(
-            "cake",
-            vec!["eggs", "grain", "soil", "chickens", "water", "flour"],
-            vec![],
-        );
-        fwd_bckwd_check(
-            "eggs",
-            vec!["chickens", "grain", "soil", "water"],
-            vec!["cake"],
-        );
-        fwd_bckwd_check("flour", vec!["grain", "soil", "water"], vec!["cake"]);
-        fwd_bckwd_check(
-            "chickens",
-            vec!["grain", "soil", "water"],
+        if self.nodes.is_empty() {
+            return stats;
+        }
+
+        stats.min_out_degree = usize::MAX;
+        stats.min_in_degree = usize::MAX;
+        let mut edges = 0;
+        for node in &self.nodes {
+            let out_degree = self.forward_dependencies.get(node).map_or(0, |d| d.len());
+            let in_degree = self.backward_dependencies.get(node).map_or(0, |d| d.len());
+            edges += out_degree;
+            stats.min_out_degree = stats.min_out_degree.min(out_degree);
+            stats.min_in_degree = stats.min_in_degree.min(in_degree);
+            if stats.max_out_degree_node.is_none() || out_degree > stats.max_out_degree {
+                stats.max_out_degree = out_degree;
+                stats.max_out_degree_node = Some(node.clone());
+            }
+            if stats.max_in_degree_node.is_none() || in_degree > stats.max_in_degree {
+                stats.max_in_degree = in_degree;
+                stats.max_in_degree_node = Some(node.clone());
+            }
+        }
+
+        // every edge contributes one out-degree and one in-degree
+        stats.mean_out_degree = edges as f64 / self.nodes.len() as f64;
+        stats.mean_in_degree = stats.mean_out_degree;
+        return stats;
+    }
+
+    /// Iterate over the nodes that directly depend on a given node.
+    /// # Arguments
+    /// * `node` - The node to get the direct dependents of.
+    /// # Returns
+    /// An iterator over the direct dependents of the node, empty if there are none.
+    /// # Remarks
+    /// Borrows from the graph without allocating.
+    pub fn direct_dependents(&self, node: &T) -> impl Iterator<Item = &T> {
+        self.backward_dependencies.get(node).into_iter().flatten()
+    }
+
+    /// Get the nodes whose removal would fragment the graph.
+    /// # Returns
+    /// The cut vertices of the graph when ignoring the direction of dependencies.
+    /// Removing any one of them increases the number of weakly connected components.
+    /// # Remarks
+    /// Uses the DFS low-link algorithm with an explicit stack, taking O(V + E) time.
+    pub fn articulation_points(&self) -> HashSet<T> {
+        let (nodes, dependencies) = self.indexed_forward_dependencies();
+        let count = nodes.len();
+        let mut neighbors = vec![Vec::new(); count];
+        for (i, targets) in dependencies.iter().enumerate() {
+            for &j in targets {
+                neighbors[i].push(j);
+                neighbors[j].push(i);
+            }
+        }
+
+        let unvisited = usize::MAX;
+        let mut discovery = vec![unvisited; count];
+        let mut low = vec![0; count];
+        let mut is_cut = vec![false; count];
+        let mut time = 0;
+        for root in 0..count {
+            if discovery[root] != unvisited {
+                continue;
+            }
+            discovery[root] = time;
+            low[root] = time;
+            time += 1;
+            let mut root_children = 0;
+
+            // each frame holds a vertex, its DFS parent and the next neighbor to visit
+            let mut stack = vec![(root, unvisited, 0)];
+            while let Some(&(vertex, parent, next)) = stack.last() {
+                if next < neighbors[vertex].len() {
+                    stack.last_mut().unwrap().2 += 1;
+                    let neighbor = neighbors[vertex][next];
+                    if discovery[neighbor] == unvisited {
+                        discovery[neighbor] = time;
+                        low[neighbor] = time;
+                        time += 1;
+                        if vertex == root {
+                            root_children += 1;
+                        }
+                        stack.push((neighbor, vertex, 0));
+                    } else if neighbor != parent {
+                        low[vertex] = low[vertex].min(discovery[neighbor]);
+                    }
+                } else {
+                    stack.pop();
+                    if parent != unvisited {
+                        low[parent] = low[parent].min(low[vertex]);
+                        if parent != root && low[vertex] >= discovery[parent] {
+                            is_cut[parent] = true;
+                        }
+                    }
+                }
+            }
+            if root_children > 1 {
+                is_cut[root] = true;
+            }
+        }
+
+        return nodes
+            .into_iter()
+            .zip(is_cut)
+            .filter(|(_, cut)| *cut)
+            .map(|(node, _)| node.clone())
+            .collect();
+    }
+
+    /// Check if a node lies on some path between two nodes.
+    /// # Arguments
+    /// * `node` - The node to look for.
+    /// * `from` - The node at the start of the path.
+    /// * `to` - The node at the end of the path.
+    /// # Returns
+    /// `true` if `from` depends on `node` and `node` depends on `to`.
+    /// `false` otherwise.
+    /// # Remarks
+    /// The endpoints themselves are on a path whenever `from` depends on `to`.
+    pub fn is_on_path(&self, node: T, from: T, to: T) -> bool {
+        if node == from || node == to {
+            return self.depends_on(&from, &to);
+        }
+        return self.depends_on(&from, &node) && self.depends_on(&node, &to);
+    }
+
+    /// Export the direct dependencies as CSV.
+    /// # Returns
+    /// A `from,to` header followed by one row per direct dependency.
+    /// Nodes without any dependencies or dependents are emitted as `node,` rows.
+    /// # Remarks
+    /// Rows are in no particular order.
+    pub fn to_csv(&self) -> String
+    where
+        T: std::fmt::Display,
+    {
+        let mut csv = String::from("from,to\n");
+        for node in &self.nodes {
+            let from = csv_escape(&node.to_string());
+            match self.forward_dependencies.get(node) {
+                Some(deps) => {
+                    for dep in deps {
+                        csv.push_str(&format!("{},{}\n", from, csv_escape(&dep.to_string())));
+                    }
+                }
+                None if !self.backward_dependencies.contains_key(node) => {
+                    csv.push_str(&format!("{},\n", from));
+                }
+                None => continue,
+            }
+        }
+        return csv;
+    }
+
+    /// Get the nodes that do not depend on any leaf.
+    /// # Returns
+    /// The non-leaf nodes from which no leaf can be reached.
+    /// # Remarks
+    /// In a consistent finite acyclic graph every node is a leaf or reaches one, so the result should always be empty.
+    /// This makes it a useful invariant probe after bulk modifications.
+    pub fn nodes_not_reaching_any_leaf(&self) -> HashSet<T> {
+        let leaves = self.get_leaves();
+        let mut reaching: HashSet<&T> = leaves.iter().collect();
+        let mut discovered: Vec<&T> = leaves.iter().collect();
+        while let Some(node) = discovered.pop() {
+            for dependent in self.backward_dependencies.get(node).into_iter().flatten() {
+                if reaching.insert(dependent) {
+                    discovered.push(dependent);
+                }
+            }
+        }
+        return self
+            .nodes
+            .iter()
+            .filter(|node| !reaching.contains(node))
+            .cloned()
+            .collect();
+    }
+
+    /// Get the fewest dependency chains needed to cover every node.
+    /// # Returns
+    /// The minimum number of chains, each a sequence of nodes where every node depends on the next, that together contain every node.
+    /// # Remarks
+    /// Chains follow the transitive closure so they may skip over nodes that belong to other chains.
+    /// This is the minimum number of sequential workers needed to process the graph as chains.
+    /// Computed via bipartite matching on the transitive closure (König's theorem) in O(V^3) time.
+    pub fn min_path_cover(&self) -> usize {
+        let (nodes, _) = self.indexed_forward_dependencies();
+        let count = nodes.len();
+        let index: HashMap<&T, usize> = nodes.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+
+        // vertices are the source, the sink, then a left and right copy of each node
+        let (source, sink) = (0, 1);
+        let mut network = FlowNetwork::new(2 * count + 2);
+        for (i, node) in nodes.iter().enumerate() {
+            network.add_edge(source, 2 + i, 1);
+            network.add_edge(2 + count + i, sink, 1);
+            for dependency in self.get_forward_dependencies(node) {
+                network.add_edge(2 + i, 2 + count + index[&dependency], 1);
+            }
+        }
+
+        // every matched pair links two nodes into the same chain
+        return count - network.max_flow(source, sink);
+    }
+
+    /// Iterate over the nodes so that every node comes before its dependencies.
+    /// # Returns
+    /// An iterator which yields the roots first and each node only once all of its dependents have been yielded.
+    /// # Remarks
+    /// Nodes are produced lazily using Kahn's algorithm over the number of direct dependents.
+    /// The particular order among independent nodes is not guaranteed.
+    pub fn reverse_topological_iter(&self) -> impl Iterator<Item = T> + '_ {
+        let mut remaining: HashMap<&T, usize> = self
+            .backward_dependencies
+            .iter()
+            .map(|(node, dependents)| (node, dependents.len()))
+            .collect();
+        let mut ready: Vec<&T> = self
+            .nodes
+            .iter()
+            .filter(|node| !remaining.contains_key(node))
+            .collect();
+        std::iter::from_fn(move || {
+            let node = ready.pop()?;
+            for dependency in self.forward_dependencies.get(node).into_iter().flatten() {
+                let count = remaining.get_mut(dependency).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    ready.push(dependency);
+                }
+            }
+            Some(node.clone())
+        })
+    }
+
+    /// Check if there is a direct dependency between two nodes.
+    fn contains_edge(&self, from: &T, to: &T) -> bool {
+        self.forward_dependencies
+            .get(from)
+            .is_some_and(|deps| deps.contains(to))
+    }
+
+    /// Check if every node and direct dependency of this graph exists in another graph.
+    /// # Arguments
+    /// * `other` - The graph which may contain this graph.
+    /// # Returns
+    /// `true` if all nodes and direct dependencies of this graph are also in `other`.
+    /// `false` otherwise.
+    pub fn is_subgraph_of(&self, other: &AcyclicDependencyGraph<T>) -> bool {
+        if !self.nodes.is_subset(&other.nodes) {
+            return false;
+        }
+        return self
+            .forward_dependencies
+            .iter()
+            .all(|(from, deps)| deps.iter().all(|to| other.contains_edge(from, to)));
+    }
+
+    /// Get the set of nodes that a given node depends on, borrowing when possible.
+    /// # Arguments
+    /// * `node` - The node to get the dependencies of.
+    /// # Returns
+    /// The same set as `get_forward_dependencies()`.
+    /// # Remarks
+    /// When every direct dependency of the node is a leaf the transitive dependencies are exactly the direct dependencies, so the internal set is borrowed.
+    /// Otherwise the set is computed and owned. A node without dependencies gets an empty owned set, which does not allocate.
+    pub fn get_forward_dependencies_cow(&self, node: T) -> Cow<'_, HashSet<T>> {
+        match self.forward_dependencies.get(&node) {
+            Some(deps)
+                if deps
+                    .iter()
+                    .all(|dep| !self.forward_dependencies.contains_key(dep)) =>
+            {
+                Cow::Borrowed(deps)
+            }
+            Some(_) => Cow::Owned(self.get_forward_dependencies(&node)),
+            None => Cow::Owned(HashSet::new()),
+        }
+    }
+
+    /// Get the index of the forward topological layer of every node.
+    /// # Returns
+    /// A map from each node to the index of its layer in `get_forward_dependency_topological_layers()`.
+    fn forward_depths(&self) -> HashMap<T, usize> {
+        let mut depths = HashMap::new();
+        let layers = self.get_forward_dependency_topological_layers();
+        for (depth, layer) in layers.into_iter().enumerate() {
+            for node in layer {
+                depths.insert(node, depth);
+            }
+        }
+        return depths;
+    }
+
+    /// Get the direct dependency that skips the most topological layers.
+    /// # Returns
+    /// `Some((from, to, skipped))` for a direct dependency with the greatest number of forward layers strictly between its endpoints.
+    /// `None` if the graph has no dependencies.
+    /// # Remarks
+    /// Ties are broken arbitrarily.
+    pub fn longest_edge(&self) -> Option<(T, T, usize)> {
+        let depths = self.forward_depths();
+        let mut longest: Option<(T, T, usize)> = None;
+        for (from, deps) in &self.forward_dependencies {
+            for to in deps {
+                let skipped = depths[from] - depths[to] - 1;
+                if longest.as_ref().is_none_or(|(_, _, most)| skipped > *most) {
+                    longest = Some((from.clone(), to.clone(), skipped));
+                }
+            }
+        }
+        return longest;
+    }
+
+    /// Get an entry for conditionally modifying the direct dependencies of a node.
+    /// # Arguments
+    /// * `node` - The node whose direct dependencies the entry refers to.
+    /// # Returns
+    /// An entry which is occupied if the node has direct dependencies and vacant otherwise.
+    /// # Remarks
+    /// Mirrors `HashMap::entry`. Dependencies added through the entry are checked like `depend_on()`.
+    pub fn dependency_entry(&mut self, node: T) -> DependencyEntry<'_, T> {
+        DependencyEntry { graph: self, node }
+    }
+
+    /// Get the nodes reachable from a given node along with their distance from it.
+    /// # Arguments
+    /// * `start` - The node to start walking from.
+    /// # Returns
+    /// Each node that `start` depends on paired with the fewest direct dependencies needed to reach it, in breadth-first order.
+    /// `start` itself comes first at depth 0. The result is empty if `start` is not in the graph.
+    pub fn forward_walk_with_depth(&self, start: T) -> Vec<(T, usize)> {
+        if !self.nodes.contains(&start) {
+            return Vec::new();
+        }
+        let mut walk = vec![(start.clone(), 0)];
+        walk.extend(dependency_map_walk_with_depth(
+            &self.forward_dependencies,
+            &start,
+        ));
+        return walk;
+    }
+
+    /// Check if a set of nodes contains all of its own dependencies.
+    /// # Arguments
+    /// * `built` - The set of nodes that have already been processed.
+    /// # Returns
+    /// `true` if every dependency of every node in `built` is also in `built`.
+    /// `false` if some node in `built` has a dependency outside of it.
+    /// # Remarks
+    /// Checking direct dependencies suffices since the property then holds transitively.
+    pub fn is_valid_frontier(&self, built: &HashSet<T>) -> bool {
+        return built.iter().all(|node| {
+            self.forward_dependencies
+                .get(node)
+                .is_none_or(|deps| deps.is_subset(built))
+        });
+    }
+
+    /// Get all nodes ranked by how many nodes they depend on.
+    /// # Returns
+    /// Every node paired with the number of nodes it transitively depends on, sorted from most to fewest.
+    /// # Remarks
+    /// The order of nodes with equal counts is not guaranteed.
+    pub fn nodes_by_descendant_count(&self) -> Vec<(T, usize)> {
+        let mut buffer = HashSet::new();
+        let mut counts: Vec<(T, usize)> = self
+            .nodes
+            .iter()
+            .map(|node| {
+                self.collect_forward_dependencies_into(node.clone(), &mut buffer);
+                (node.clone(), buffer.len())
+            })
+            .collect();
+        counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        return counts;
+    }
+
+    /// Get the nodes and direct dependencies of the graph in sorted order.
+    /// # Returns
+    /// The sorted nodes and the sorted `(from, to)` direct dependency pairs.
+    fn canonical_form(&self) -> (Vec<&T>, Vec<(&T, &T)>)
+    where
+        T: Ord,
+    {
+        let mut nodes: Vec<&T> = self.nodes.iter().collect();
+        nodes.sort();
+        let mut edges: Vec<(&T, &T)> = self
+            .forward_dependencies
+            .iter()
+            .flat_map(|(from, deps)| deps.iter().map(move |to| (from, to)))
+            .collect();
+        edges.sort();
+        return (nodes, edges);
+    }
+
+    /// Get a hash of the nodes and direct dependencies of the graph.
+    /// # Returns
+    /// A 64-bit fingerprint which is equal for graphs with the same nodes and direct dependencies.
+    /// # Remarks
+    /// The canonical (sorted) structure is hashed with a fixed-key hasher, so the value is stable across runs of the same build.
+    /// It is not guaranteed to be stable across Rust versions.
+    pub fn structural_fingerprint(&self) -> u64
+    where
+        T: Ord,
+    {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        self.canonical_form().hash(&mut hasher);
+        return hasher.finish();
+    }
+
+    /// Get the forward topological layers as deterministically ordered batches.
+    /// # Returns
+    /// The forward topological layers, each sorted in ascending order.
+    /// Every node in a batch depends only on nodes in earlier batches so the nodes of a batch can be processed concurrently.
+    pub fn schedule_batches(&self) -> Vec<Vec<T>>
+    where
+        T: Ord,
+    {
+        return self
+            .get_forward_dependency_topological_layers()
+            .into_iter()
+            .map(|layer| {
+                let mut batch: Vec<T> = layer.into_iter().collect();
+                batch.sort();
+                batch
+            })
+            .collect();
+    }
+
+    /// Get the nodes that would no longer be reachable from any root if a node were removed.
+    /// # Arguments
+    /// * `node` - The node whose removal to analyze.
+    /// # Returns
+    /// The nodes, other than `node` itself, which no current root other than `node` depends on once `node` is removed.
+    pub fn orphans_if_removed(&self, node: T) -> HashSet<T> {
+        let roots = self.get_roots();
+        let mut reachable: HashSet<&T> = roots.iter().filter(|root| **root != node).collect();
+        let mut discovered: Vec<&T> = reachable.iter().copied().collect();
+        while let Some(current) = discovered.pop() {
+            for dependency in self.forward_dependencies.get(current).into_iter().flatten() {
+                if *dependency != node && reachable.insert(dependency) {
+                    discovered.push(dependency);
+                }
+            }
+        }
+        return self
+            .nodes
+            .iter()
+            .filter(|other| **other != node && !reachable.contains(other))
+            .cloned()
+            .collect();
+    }
+
+    /// Get the set of nodes that each root depends on.
+    /// # Returns
+    /// A map from every root to the set of nodes it transitively depends on.
+    /// # Remarks
+    /// Nodes shared between roots appear in the set of each root, so intersecting sets finds shared dependencies.
+    pub fn dependencies_by_root(&self) -> HashMap<T, HashSet<T>> {
+        return self
+            .get_roots()
+            .into_iter()
+            .map(|root| {
+                let dependencies = self.get_forward_dependencies(&root);
+                (root, dependencies)
+            })
+            .collect();
+    }
+
+    /// Get the leaves that must be provided to satisfy a node.
+    /// # Arguments
+    /// * `target` - The node to satisfy.
+    /// # Returns
+    /// The leaves that `target` depends on, or just `target` if it is itself a leaf.
+    /// The set is empty if `target` is not in the graph.
+    /// # Remarks
+    /// Every dependency is mandatory in this graph so the full leaf closure is the only minimal set.
+    /// With alternative (OR-style) dependencies there could be several smaller sets instead.
+    pub fn minimal_leaf_requirements(&self, target: T) -> HashSet<T> {
+        if !self.nodes.contains(&target) {
+            return HashSet::new();
+        }
+        if !self.forward_dependencies.contains_key(&target) {
+            return HashSet::from([target]);
+        }
+        let mut requirements = self.get_forward_dependencies(&target);
+        requirements.retain(|node| !self.forward_dependencies.contains_key(node));
+        return requirements;
+    }
+
+    /// Accumulate shortest path betweenness for nodes and direct dependencies (Brandes' algorithm).
+    /// # Returns
+    /// For every node and every direct dependency, the sum over all ordered pairs of nodes of the fraction of shortest forward paths between them passing through it.
+    /// Paths do not pass through their own endpoints.
+    fn shortest_path_betweenness(&self) -> (HashMap<T, f64>, HashMap<(T, T), f64>) {
+        let (nodes, dependencies) = self.indexed_forward_dependencies();
+        let count = nodes.len();
+        let mut node_scores = vec![0.0; count];
+        let mut edge_scores: HashMap<(usize, usize), f64> = HashMap::new();
+
+        for source in 0..count {
+            // breadth-first search counting the shortest paths to every reachable node
+            let mut order = Vec::new();
+            let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); count];
+            let mut paths = vec![0.0; count];
+            let mut distance = vec![usize::MAX; count];
+            paths[source] = 1.0;
+            distance[source] = 0;
+            let mut queue = VecDeque::from([source]);
+            while let Some(vertex) = queue.pop_front() {
+                order.push(vertex);
+                for &next in &dependencies[vertex] {
+                    if distance[next] == usize::MAX {
+                        distance[next] = distance[vertex] + 1;
+                        queue.push_back(next);
+                    }
+                    if distance[next] == distance[vertex] + 1 {
+                        paths[next] += paths[vertex];
+                        predecessors[next].push(vertex);
+                    }
+                }
+            }
+
+            // accumulate dependencies in order of decreasing distance
+            let mut dependency = vec![0.0; count];
+            for &vertex in order.iter().rev() {
+                for &predecessor in &predecessors[vertex] {
+                    let share = paths[predecessor] / paths[vertex] * (1.0 + dependency[vertex]);
+                    *edge_scores.entry((predecessor, vertex)).or_default() += share;
+                    dependency[predecessor] += share;
+                }
+                if vertex != source {
+                    node_scores[vertex] += dependency[vertex];
+                }
+            }
+        }
+
+        let node_scores = nodes
+            .iter()
+            .map(|n| (*n).clone())
+            .zip(node_scores)
+            .collect();
+        let mut edges = HashMap::new();
+        for (i, targets) in dependencies.iter().enumerate() {
+            for &j in targets {
+                let score = edge_scores.get(&(i, j)).copied().unwrap_or_default();
+                edges.insert((nodes[i].clone(), nodes[j].clone()), score);
+            }
+        }
+        return (node_scores, edges);
+    }
+
+    /// Get how many shortest paths between nodes pass through each direct dependency.
+    /// # Returns
+    /// A map from every direct dependency `(from, to)` to the sum, over all ordered pairs of nodes, of the fraction of shortest forward paths between them that use it.
+    /// # Remarks
+    /// High scores mark structurally critical dependencies.
+    /// Uses Brandes' algorithm in O(V * E) time.
+    pub fn edge_betweenness(&self) -> HashMap<(T, T), f64> {
+        return self.shortest_path_betweenness().1;
+    }
+
+    /// Check if the dependencies fully determine the processing order.
+    /// # Returns
+    /// `true` if there is exactly one order of the nodes in which every node comes after its dependencies.
+    /// `false` if independent nodes could be swapped.
+    /// # Remarks
+    /// Runs Kahn's algorithm and checks that exactly one node is ready at every step.
+    /// Equivalently, the graph contains a path through every node.
+    pub fn has_unique_topological_order(&self) -> bool {
+        let mut remaining: HashMap<&T, usize> = self
+            .forward_dependencies
+            .iter()
+            .map(|(node, deps)| (node, deps.len()))
+            .collect();
+        let mut ready: Vec<&T> = self
+            .nodes
+            .iter()
+            .filter(|node| !remaining.contains_key(node))
+            .collect();
+        while let Some(node) = ready.pop() {
+            if !ready.is_empty() {
+                return false;
+            }
+            for dependent in self.backward_dependencies.get(node).into_iter().flatten() {
+                let count = remaining.get_mut(dependent).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    ready.push(dependent);
+                }
+            }
+        }
+        return true;
+    }
+
+    /// Keep only the direct dependencies matching a predicate.
+    /// # Arguments
+    /// * `f` - Called as `f(from, to)` for every direct dependency, which is removed if it returns `false`.
+    /// # Remarks
+    /// Nodes are kept even if they lose all of their dependencies and dependents.
+    pub fn retain_edges<F: FnMut(&T, &T) -> bool>(&mut self, mut f: F) {
+        let mut removed = Vec::new();
+        for (from, deps) in &mut self.forward_dependencies {
+            deps.retain(|to| match f(from, to) {
+                true => true,
+                false => {
+                    removed.push((from.clone(), to.clone()));
+                    false
+                }
+            });
+        }
+        self.forward_dependencies.retain(|_, deps| !deps.is_empty());
+
+        // mirror the removals in the backward dependencies
+        for (from, to) in removed {
+            if let Some(dependents) = self.backward_dependencies.get_mut(&to) {
+                dependents.remove(&from);
+                if dependents.is_empty() {
+                    self.backward_dependencies.remove(&to);
+                }
+            }
+        }
+
+        #[cfg(debug_assertions)]
+        self.assert_consistent();
+    }
+
+    /// Collapse groups of nodes into single nodes.
+    /// # Arguments
+    /// * `group_of` - Maps each node to the group it belongs to.
+    /// # Returns
+    /// A graph with one node per group where a group depends on another if any of its nodes directly depends on a node of the other.
+    /// Dependencies within a group are dropped.
+    /// # Panics
+    /// If the grouping creates a circular dependency between groups.
+    pub fn condense<G, F>(&self, group_of: F) -> AcyclicDependencyGraph<G>
+    where
+        G: Eq + Hash + Copy,
+        F: Fn(&T) -> G,
+    {
+        let mut condensed = AcyclicDependencyGraph::new();
+        for node in &self.nodes {
+            condensed.nodes.insert(group_of(node));
+        }
+
+        // many original dependencies can join the same pair of groups
+        let mut group_edges: HashSet<(G, G)> = HashSet::new();
+        for (from, deps) in &self.forward_dependencies {
+            let from_group = group_of(from);
+            for to in deps {
+                let to_group = group_of(to);
+                if from_group != to_group {
+                    group_edges.insert((from_group, to_group));
+                }
+            }
+        }
+        for (from_group, to_group) in group_edges {
+            if let Err(error) = condensed.depend_on(from_group, to_group) {
+                panic!("grouping is not acyclic: {}", error);
+            }
+        }
+        return condensed;
+    }
+
+    /// Add a dependency between two nodes and report the newly reachable dependencies.
+    /// # Arguments
+    /// * `from` - The node that depends on the other node.
+    /// * `to` - The node that is depended on.
+    /// # Returns
+    /// `Ok(gained)` with the nodes that `from` depends on now but did not depend on before.
+    /// `Err(DependencyError)` as for `depend_on()`, in which case the graph is unchanged.
+    /// # Remarks
+    /// Useful for invalidating exactly the cached results affected by the new dependency.
+    pub fn depend_on_tracked(&mut self, from: T, to: T) -> Result<HashSet<T>, DependencyError> {
+        let before = self.get_forward_dependencies(&from);
+        let mut gained = self.get_forward_dependencies(&to);
+        gained.insert(to.clone());
+        gained.retain(|node| !before.contains(node));
+        self.depend_on(from, to)?;
+        return Ok(gained);
+    }
+
+    /// Get the direct dependencies ordered so that every dependency on a node comes before the dependencies of that node.
+    /// # Returns
+    /// All direct dependencies `(from, to)` where, for each `(a, b)`, every `(x, a)` appears earlier.
+    /// # Remarks
+    /// Edges are grouped by their source in `reverse_topological_iter()` order.
+    pub fn edges_topologically(&self) -> Vec<(T, T)> {
+        let mut edges = Vec::new();
+        for from in self.reverse_topological_iter() {
+            if let Some(deps) = self.forward_dependencies.get(&from) {
+                for to in deps {
+                    edges.push((from.clone(), to.clone()));
+                }
+            }
+        }
+        return edges;
+    }
+
+    /// Get the nodes affected by a change to some nodes.
+    /// # Arguments
+    /// * `changed` - The nodes that changed.
+    /// # Returns
+    /// The changed nodes together with every node that depends on any of them.
+    /// This is exactly the set of nodes that must be rebuilt.
+    pub fn blast_radius(&self, changed: &[T]) -> HashSet<T> {
+        let mut affected: HashSet<T> = changed.iter().cloned().collect();
+        let mut discovered: Vec<&T> = changed.iter().collect();
+        while let Some(node) = discovered.pop() {
+            for dependent in self.backward_dependencies.get(node).into_iter().flatten() {
+                if affected.insert(dependent.clone()) {
+                    discovered.push(dependent);
+                }
+            }
+        }
+        return affected;
+    }
+
+    /// Get a single forward topological layer by index.
+    /// # Arguments
+    /// * `depth` - The index of the layer in `get_forward_dependency_topological_layers()`.
+    /// # Returns
+    /// The nodes of the layer, empty if `depth` is past the last layer.
+    /// # Remarks
+    /// Layers after `depth` are never computed.
+    pub fn nodes_at_forward_depth(&self, depth: usize) -> HashSet<T> {
+        let mut remaining: HashMap<&T, usize> = self
+            .forward_dependencies
+            .iter()
+            .map(|(node, deps)| (node, deps.len()))
+            .collect();
+        let mut layer: Vec<&T> = self
+            .nodes
+            .iter()
+            .filter(|node| !remaining.contains_key(node))
+            .collect();
+        for _ in 0..depth {
+            let mut next = Vec::new();
+            for node in layer {
+                for dependent in self.backward_dependencies.get(node).into_iter().flatten() {
+                    let count = remaining.get_mut(dependent).unwrap();
+                    *count -= 1;
+                    if *count == 0 {
+                        next.push(dependent);
+                    }
+                }
+            }
+            layer = next;
+        }
+        return layer.into_iter().cloned().collect();
+    }
+
+    /// Get the number of times an existing dependency was added again.
+    /// # Returns
+    /// How many calls to `depend_on()` named a dependency that already existed, since creation or the last `clear()`.
+    /// # Remarks
+    /// A high count from a loader hints at duplicated input data.
+    pub fn duplicate_edge_insert_count(&self) -> usize {
+        self.duplicate_edge_inserts
+    }
+
+    /// Get the nodes that depend on a given node along with their distance from it.
+    /// # Arguments
+    /// * `node` - The node to get the dependents of.
+    /// # Returns
+    /// Each node that depends on `node` paired with the fewest direct dependencies leading from it to `node`, nearest first.
+    pub fn ancestors_by_depth(&self, node: T) -> Vec<(T, usize)> {
+        return dependency_map_walk_with_depth(&self.backward_dependencies, &node);
+    }
+
+    /// Get the pairs of nodes with identical direct dependencies and dependents.
+    /// # Returns
+    /// Every unordered pair of distinct nodes that have the same direct dependencies and the same direct dependents.
+    /// # Remarks
+    /// Twins are interchangeable in the structure of the graph, making them candidates for deduplication.
+    /// Isolated nodes are twins of each other. Compares all pairs in O(V^2) time.
+    pub fn structural_twins(&self) -> Vec<(T, T)> {
+        let nodes: Vec<&T> = self.nodes.iter().collect();
+        let mut twins = Vec::new();
+        for (i, a) in nodes.iter().enumerate() {
+            for b in &nodes[i + 1..] {
+                if self.forward_dependencies.get(*a) == self.forward_dependencies.get(*b)
+                    && self.backward_dependencies.get(*a) == self.backward_dependencies.get(*b)
+                {
+                    twins.push(((*a).clone(), (*b).clone()));
+                }
+            }
+        }
+        return twins;
+    }
+
+    /// Check if a node depends on another without using the direct dependency between them.
+    /// # Arguments
+    /// * `from` - The node that depends on the other node.
+    /// * `to` - The node that is depended on.
+    /// # Returns
+    /// `true` if some path from `from` to `to` has at least one intermediate node.
+    fn depends_on_indirectly(&self, from: &T, to: &T) -> bool {
+        let mut visited = HashSet::new();
+        let mut discovered: Vec<&T> = self
+            .forward_dependencies
+            .get(from)
+            .into_iter()
+            .flatten()
+            .filter(|dep| *dep != to)
+            .collect();
+        while let Some(node) = discovered.pop() {
+            for dependency in self.forward_dependencies.get(node).into_iter().flatten() {
+                if dependency == to {
+                    return true;
+                }
+                if visited.insert(dependency) {
+                    discovered.push(dependency);
+                }
+            }
+        }
+        return false;
+    }
+
+    /// Get the direct dependencies that are also implied through other nodes.
+    /// # Returns
+    /// Every direct dependency `(from, to)` where `from` also depends on `to` through other nodes.
+    /// # Remarks
+    /// These are both the dependencies a transitive reduction drops and the ones whose reversal would close a loop with the other path,
+    /// so `irreversible_edges()` and `transitive_reduction_with_removed()` share this search.
+    fn redundant_edges(&self) -> Vec<(&T, &T)> {
+        let mut edges = Vec::new();
+        for (from, deps) in &self.forward_dependencies {
+            for to in deps {
+                if self.depends_on_indirectly(from, to) {
+                    edges.push((from, to));
+                }
+            }
+        }
+        return edges;
+    }
+
+    /// Get the direct dependencies whose reversal would create a circular dependency.
+    /// # Returns
+    /// Every direct dependency `(from, to)` where `from` also depends on `to` through other nodes.
+    /// Reversing such a dependency would close a loop with that other path.
+    pub fn irreversible_edges(&self) -> Vec<(T, T)> {
+        return self
+            .redundant_edges()
+            .into_iter()
+            .map(|(from, to)| (from.clone(), to.clone()))
+            .collect();
+    }
+
+    /// Get a pseudo-random order in which every node comes after its dependencies.
+    /// # Arguments
+    /// * `seed` - The seed of the pseudo-random choices.
+    /// # Returns
+    /// Every node, each after all of its dependencies.
+    /// # Remarks
+    /// Runs Kahn's algorithm, choosing among the ready nodes with a generator seeded by `seed`.
+    /// Ready nodes are kept in sorted order so that the same graph and seed always produce the same order.
+    pub fn random_topological_order(&self, seed: u64) -> Vec<T>
+    where
+        T: Ord,
+    {
+        let mut state = seed;
+        let mut remaining: HashMap<&T, usize> = self
+            .forward_dependencies
+            .iter()
+            .map(|(node, deps)| (node, deps.len()))
+            .collect();
+        let mut ready: Vec<&T> = self
+            .nodes
+            .iter()
+            .filter(|node| !remaining.contains_key(node))
+            .collect();
+        ready.sort();
+
+        let mut order = Vec::with_capacity(self.nodes.len());
+        while !ready.is_empty() {
+            let choice = (splitmix64(&mut state) % ready.len() as u64) as usize;
+            let node = ready.remove(choice);
+            order.push(node.clone());
+
+            let mut unlocked = Vec::new();
+            for dependent in self.backward_dependencies.get(node).into_iter().flatten() {
+                let count = remaining.get_mut(dependent).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    unlocked.push(dependent);
+                }
+            }
+            unlocked.sort();
+            ready.extend(unlocked);
+        }
+        return order;
+    }
+
+    /// Get the number of nodes that depend on a given node.
+    /// # Arguments
+    /// * `node` - The node to count the dependents of.
+    /// # Returns
+    /// The size of `get_backward_dependencies()` for the node.
+    /// A high count means a break in this node affects many consumers.
+    pub fn transitive_dependent_count(&self, node: T) -> usize {
+        return self.get_backward_dependencies(&node).len();
+    }
+
+    /// Get all nodes ranked by how many nodes depend on them.
+    /// # Returns
+    /// Every node paired with its `transitive_dependent_count()`, sorted from most to fewest.
+    /// # Remarks
+    /// The order of nodes with equal counts is not guaranteed.
+    pub fn nodes_by_dependent_count(&self) -> Vec<(T, usize)> {
+        let mut counts: Vec<(T, usize)> = self
+            .nodes
+            .iter()
+            .map(|node| (node.clone(), self.transitive_dependent_count(node.clone())))
+            .collect();
+        counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        return counts;
+    }
+
+    /// Check if an order of the nodes puts every dependency before its dependents.
+    /// # Arguments
+    /// * `order` - The proposed order of the nodes.
+    /// # Returns
+    /// `true` if `order` contains every node exactly once and each node appears after all of its dependencies.
+    /// `false` otherwise.
+    pub fn is_valid_topological_order(&self, order: &[T]) -> bool {
+        if order.len() != self.nodes.len() {
+            return false;
+        }
+        let mut position: HashMap<&T, usize> = HashMap::with_capacity(order.len());
+        for (i, node) in order.iter().enumerate() {
+            if !self.nodes.contains(node) || position.insert(node, i).is_some() {
+                return false;
+            }
+        }
+        return self
+            .forward_dependencies
+            .iter()
+            .all(|(from, deps)| deps.iter().all(|to| position[to] < position[from]));
+    }
+
+    /// Get the nodes needed by both of two nodes.
+    /// # Arguments
+    /// * `a` - The first node.
+    /// * `b` - The second node.
+    /// # Returns
+    /// The intersection of the dependencies of `a` and `b`, where each node counts as needing itself.
+    /// # Remarks
+    /// Endpoints are included: if `a` depends on `b` then `b` is shared, and `a` is shared with itself.
+    pub fn shared_between(&self, a: T, b: T) -> HashSet<T> {
+        let mut needed_by_a = self.get_forward_dependencies(&a);
+        needed_by_a.insert(a);
+        let mut shared = self.get_forward_dependencies(&b);
+        shared.insert(b);
+        shared.retain(|node| needed_by_a.contains(node));
+        return shared;
+    }
+
+    /// Export the direct dependencies as a Mermaid flowchart.
+    /// # Returns
+    /// A `flowchart TD` declaring every node as `nN["label"]`, followed by one `nN --> nM` line per direct dependency.
+    /// # Remarks
+    /// Identifiers are numbered in order of the node labels, so distinct nodes never share an identifier and the output is stable.
+    pub fn to_mermaid(&self) -> String
+    where
+        T: std::fmt::Display,
+    {
+        let mut labeled: Vec<(String, &T)> = self
+            .nodes
+            .iter()
+            .map(|node| (node.to_string(), node))
+            .collect();
+        labeled.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let ids: HashMap<&T, usize> = labeled
+            .iter()
+            .enumerate()
+            .map(|(i, (_, node))| (*node, i))
+            .collect();
+
+        let mut mermaid = String::from("flowchart TD\n");
+        for (i, (label, _)) in labeled.iter().enumerate() {
+            mermaid.push_str(&format!("    n{}[{}]\n", i, mermaid_label(label)));
+        }
+        let mut edges: Vec<(usize, usize)> = Vec::new();
+        for (from, deps) in &self.forward_dependencies {
+            for to in deps {
+                edges.push((ids[from], ids[to]));
+            }
+        }
+        edges.sort_unstable();
+        for (from, to) in edges {
+            mermaid.push_str(&format!("    n{} --> n{}\n", from, to));
+        }
+        return mermaid;
+    }
+
+    /// Get the mean forward topological layer index of the nodes.
+    /// # Returns
+    /// The arithmetic mean of the index of each node's layer in `get_forward_dependency_topological_layers()`, or 0.0 for an empty graph.
+    /// # Remarks
+    /// Summarizes how deep the dependency structure is in a single number.
+    pub fn average_depth(&self) -> f64 {
+        if self.nodes.is_empty() {
+            return 0.0;
+        }
+        let total: usize = self.forward_depths().values().sum();
+        return total as f64 / self.nodes.len() as f64;
+    }
+
+    /// Get a longest chain of direct dependencies in the graph.
+    /// # Returns
+    /// The nodes of a path with the most nodes, starting at a root and ending at a leaf, where each node directly depends on the next.
+    /// The path is empty for an empty graph.
+    /// # Remarks
+    /// When several paths are equally long an arbitrary one is returned.
+    pub fn longest_path(&self) -> Vec<T> {
+        let depths = self.forward_depths();
+        let mut current = match depths.iter().max_by_key(|(_, depth)| **depth) {
+            Some((node, _)) => node,
+            None => return Vec::new(),
+        };
+
+        // a node at depth d always has a direct dependency at depth d - 1
+        let mut path = vec![current.clone()];
+        while let Some(deps) = self.forward_dependencies.get(current) {
+            current = deps
+                .iter()
+                .find(|dep| depths[*dep] + 1 == depths[current])
+                .unwrap();
+            path.push(current.clone());
+        }
+        return path;
+    }
+
+    /// Get the nodes in an order where every node comes after all of its dependencies.
+    /// # Remarks
+    /// Kahn's algorithm over the number of direct dependencies, taking O(V + E) time.
+    fn leaves_first_order(&self) -> Vec<&T> {
+        let mut remaining: HashMap<&T, usize> = self
+            .forward_dependencies
+            .iter()
+            .map(|(node, deps)| (node, deps.len()))
+            .collect();
+        let mut order: Vec<&T> = self
+            .nodes
+            .iter()
+            .filter(|node| !remaining.contains_key(node))
+            .collect();
+        let mut next = 0;
+        while next < order.len() {
+            let node = order[next];
+            next += 1;
+            for dependent in self.backward_dependencies.get(node).into_iter().flatten() {
+                let count = remaining.get_mut(dependent).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    order.push(dependent);
+                }
+            }
+        }
+        return order;
+    }
+
+    /// Precompute the dependencies of every node for constant time queries.
+    /// # Returns
+    /// An index answering `depends_on()` queries with a single bit test.
+    /// # Remarks
+    /// The index stores one bit per pair of nodes, trading O(V^2) memory for query speed.
+    /// It is a snapshot and does not reflect later changes to the graph.
+    pub fn build_reachability_index(&self) -> ReachabilityIndex<T> {
+        let order = self.leaves_first_order();
+        let index: HashMap<T, usize> = order
+            .iter()
+            .enumerate()
+            .map(|(i, n)| ((*n).clone(), i))
+            .collect();
+        let words = order.len().div_ceil(64);
+        let mut bits = vec![0u64; words * order.len()];
+
+        // dependencies come first so their rows are complete when merged into their dependents
+        for (i, node) in order.iter().enumerate() {
+            for dependency in self.forward_dependencies.get(*node).into_iter().flatten() {
+                let j = index[dependency];
+                bits[i * words + j / 64] |= 1 << (j % 64);
+                for word in 0..words {
+                    bits[i * words + word] |= bits[j * words + word];
+                }
+            }
+        }
+        return ReachabilityIndex { index, words, bits };
+    }
+
+    /// Get the pairs of nodes where neither depends on the other.
+    /// # Returns
+    /// Every unordered pair of distinct nodes with no path between them in either direction.
+    /// Such pairs can be processed concurrently.
+    /// # Remarks
+    /// The number of pairs grows as O(V^2) so this is intended for modest graphs.
+    pub fn incomparable_pairs(&self) -> Vec<(T, T)> {
+        let index = self.build_reachability_index();
+        let nodes: Vec<&T> = self.nodes.iter().collect();
+        let mut pairs = Vec::new();
+        for (i, a) in nodes.iter().enumerate() {
+            for b in &nodes[i + 1..] {
+                if !index.reaches(a, b) && !index.reaches(b, a) {
+                    pairs.push(((*a).clone(), (*b).clone()));
+                }
+            }
+        }
+        return pairs;
+    }
+
+    /// Check if a direct dependency is implied by other dependencies.
+    /// # Arguments
+    /// * `from` - The node that depends on the other node.
+    /// * `to` - The node that is depended on.
+    /// # Returns
+    /// `true` if `from` directly depends on `to` and also depends on it through other nodes, so removing the direct dependency changes no reachability.
+    /// `false` otherwise, including when there is no direct dependency.
+    pub fn is_edge_redundant(&self, from: &T, to: &T) -> bool {
+        return self.contains_edge(from, to) && self.depends_on_indirectly(from, to);
+    }
+
+    /// Get the forward topological layers of a node and its dependencies.
+    /// # Arguments
+    /// * `root` - The node whose dependencies to lay out.
+    /// # Returns
+    /// The forward topological layers of the sub-graph made of `root` and every node it depends on.
+    /// `root` is alone in the last layer. The result is empty if `root` is not in the graph.
+    /// # Remarks
+    /// The sub-graph contains all dependencies of its nodes, so each node keeps the layer index it has in the whole graph.
+    pub fn subtree_forward_layers(&self, root: T) -> Vec<HashSet<T>> {
+        if !self.nodes.contains(&root) {
+            return Vec::new();
+        }
+        let mut subtree = self.get_forward_dependencies(&root);
+        subtree.insert(root.clone());
+        let mut layers: Vec<HashSet<T>> = self
+            .get_forward_dependency_topological_layers()
+            .into_iter()
+            .map(|mut layer| {
+                layer.retain(|node| subtree.contains(node));
+                layer
+            })
+            .collect();
+        layers.retain(|layer| !layer.is_empty());
+        return layers;
+    }
+
+    /// Get the node where the shortest paths to two nodes split.
+    /// # Arguments
+    /// * `from` - The node both paths start at.
+    /// * `a` - The end of the first path.
+    /// * `b` - The end of the second path.
+    /// # Returns
+    /// `Some` last node shared by a shortest path from `from` to `a` and a shortest path from `from` to `b`.
+    /// This is `from` if the paths split immediately, or `a` if the path to `b` passes through `a`.
+    /// `None` if `a` or `b` cannot be reached from `from`.
+    /// # Remarks
+    /// Both paths are taken from a single breadth-first search tree.
+    /// When several shortest paths exist one of them is chosen arbitrarily.
+    pub fn divergence_point(&self, from: T, a: T, b: T) -> Option<T> {
+        let mut parents: HashMap<&T, &T> = HashMap::new();
+        let mut queue = VecDeque::from([&from]);
+        while let Some(node) = queue.pop_front() {
+            for dependency in self.forward_dependencies.get(node).into_iter().flatten() {
+                if *dependency != from && !parents.contains_key(dependency) {
+                    parents.insert(dependency, node);
+                    queue.push_back(dependency);
+                }
+            }
+        }
+
+        let path_to = |target: &T| -> Option<Vec<&T>> {
+            let mut path = vec![parents.get_key_value(target).map(|(node, _)| *node)?];
+            while let Some(parent) = parents.get(path[path.len() - 1]) {
+                path.push(parent);
+            }
+            path.reverse();
+            return Some(path);
+        };
+        let path_to_a = match a == from {
+            true => vec![&from],
+            false => path_to(&a)?,
+        };
+        let path_to_b = match b == from {
+            true => vec![&from],
+            false => path_to(&b)?,
+        };
+        return path_to_a
+            .iter()
+            .zip(&path_to_b)
+            .take_while(|(x, y)| x == y)
+            .last()
+            .map(|(node, _)| (*node).clone());
+    }
+
+    /// Release excess memory held by the graph.
+    /// # Remarks
+    /// Shrinks the node set, both dependency maps and every dependency set as much as possible.
+    /// Useful for long-lived graphs after many removals.
+    pub fn shrink_to_fit(&mut self) {
+        self.nodes.shrink_to_fit();
+        for map in [
+            &mut self.forward_dependencies,
+            &mut self.backward_dependencies,
+        ] {
+            map.shrink_to_fit();
+            for deps in map.values_mut() {
+                deps.shrink_to_fit();
+            }
+        }
+    }
+
+    /// Combine several graphs under a common node type.
+    /// # Arguments
+    /// * `graphs` - The graphs to combine.
+    /// * `key` - Maps each node of the graphs to its node in the combined graph.
+    /// # Returns
+    /// `Ok(graph)` containing every mapped node and every mapped direct dependency of all graphs.
+    /// `Err(DependencyError::SelfReference)` if both ends of a dependency map to the same node.
+    /// `Err(DependencyError::CircularDependency)` if the combined dependencies would form a cycle.
+    pub fn merge_mapped<U, F>(
+        graphs: &[&AcyclicDependencyGraph<T>],
+        key: F,
+    ) -> Result<AcyclicDependencyGraph<U>, DependencyError>
+    where
+        U: Eq + Hash + Copy,
+        F: Fn(&T) -> U,
+    {
+        let mut merged = AcyclicDependencyGraph::new();
+
+        // dependencies can coincide once mapped, within and across graphs
+        let mut edges: HashSet<(U, U)> = HashSet::new();
+        for graph in graphs {
+            for node in &graph.nodes {
+                merged.nodes.insert(key(node));
+            }
+            for (from, deps) in &graph.forward_dependencies {
+                for to in deps {
+                    edges.insert((key(from), key(to)));
+                }
+            }
+        }
+        for (from, to) in edges {
+            merged.depend_on(from, to)?;
+        }
+        return Ok(merged);
+    }
+
+    /// Check if a node has no dependencies.
+    /// # Arguments
+    /// * `node` - The node to check.
+    /// # Returns
+    /// `true` if the node is in the graph and has no dependencies.
+    /// `false` otherwise.
+    /// # Remarks
+    /// Takes constant time, unlike building the whole set with `get_leaves()`.
+    pub fn is_leaf(&self, node: &T) -> bool {
+        self.nodes.contains(node) && !self.forward_dependencies.contains_key(node)
+    }
+
+    /// Check if a node has no dependents.
+    /// # Arguments
+    /// * `node` - The node to check.
+    /// # Returns
+    /// `true` if the node is in the graph and has no dependents.
+    /// `false` otherwise.
+    /// # Remarks
+    /// Takes constant time, unlike building the whole set with `get_roots()`.
+    pub fn is_root(&self, node: &T) -> bool {
+        self.nodes.contains(node) && !self.backward_dependencies.contains_key(node)
+    }
+
+    /// Get the number of nodes in each forward topological layer.
+    /// # Returns
+    /// The size of each layer of `get_forward_dependency_topological_layers()`, in the same order.
+    /// Each size is how many nodes can be processed concurrently at that stage.
+    pub fn layer_widths(&self) -> Vec<usize> {
+        return self
+            .get_forward_dependency_topological_layers()
+            .iter()
+            .map(|layer| layer.len())
+            .collect();
+    }
+
+    /// Get the earliest layer a node can be scheduled in given minimum layers for some nodes.
+    /// # Arguments
+    /// * `node` - The node to schedule.
+    /// * `floor` - The earliest layer allowed for each constrained node.
+    /// # Returns
+    /// The greater of the node's own floor and one more than the layer of each of its dependencies, where every dependency is scheduled the same way.
+    /// Without any floors this is the node's index in `get_forward_dependency_topological_layers()`.
+    /// # Remarks
+    /// Floors model external constraints such as resources becoming available late, and push back every dependent.
+    pub fn earliest_layer_with_floor(&self, node: T, floor: HashMap<T, usize>) -> usize {
+        let mut layers: HashMap<&T, usize> = HashMap::new();
+        for current in self.leaves_first_order() {
+            let mut layer = floor.get(current).copied().unwrap_or(0);
+            for dependency in self.forward_dependencies.get(current).into_iter().flatten() {
+                layer = layer.max(layers[dependency] + 1);
+            }
+            layers.insert(current, layer);
+        }
+        return match layers.get(&node) {
+            Some(layer) => *layer,
+            None => floor.get(&node).copied().unwrap_or(0),
+        };
+    }
+
+    /// Get the nodes with more direct dependencies and dependents than a threshold.
+    /// # Arguments
+    /// * `threshold` - The largest acceptable combined number of direct dependencies and dependents.
+    /// # Returns
+    /// The nodes whose number of direct dependencies plus number of direct dependents exceeds `threshold`.
+    /// # Remarks
+    /// Such hub nodes are architectural hotspots worth refactoring.
+    pub fn high_degree_nodes(&self, threshold: usize) -> HashSet<T> {
+        let degree = |node: &T| {
+            self.forward_dependencies.get(node).map_or(0, |d| d.len())
+                + self.backward_dependencies.get(node).map_or(0, |d| d.len())
+        };
+        return self
+            .nodes
+            .iter()
+            .filter(|node| degree(node) > threshold)
+            .cloned()
+            .collect();
+    }
+
+    /// Get the transitive reduction of the graph along with the dependencies it drops.
+    /// # Returns
+    /// A new graph with the same nodes and reachability but no redundant direct dependencies, and the list of direct dependencies that were removed.
+    /// # Remarks
+    /// A direct dependency is redundant when it is implied by other dependencies, see `is_edge_redundant()`.
+    /// The transitive reduction of an acyclic graph is unique so all redundant dependencies can be removed at once.
+    pub fn transitive_reduction_with_removed(&self) -> (AcyclicDependencyGraph<T>, Vec<(T, T)>) {
+        let redundant: HashSet<(&T, &T)> = self.redundant_edges().into_iter().collect();
+        let mut reduced = self.clone();
+        reduced.retain_edges(|from, to| !redundant.contains(&(from, to)));
+        let removed = redundant
+            .into_iter()
+            .map(|(from, to)| (from.clone(), to.clone()))
+            .collect();
+        return (reduced, removed);
+    }
+
+    /// Check if two nodes share no dependencies.
+    /// # Arguments
+    /// * `a` - The first node.
+    /// * `b` - The second node.
+    /// # Returns
+    /// `true` if no node is depended on by both `a` and `b`.
+    /// `false` if they have a dependency in common.
+    /// # Remarks
+    /// Only the dependencies are compared, not `a` and `b` themselves.
+    pub fn subtrees_disjoint(&self, a: T, b: T) -> bool {
+        let needed_by_a = self.get_forward_dependencies(&a);
+        return self.get_forward_dependencies(&b).is_disjoint(&needed_by_a);
+    }
+
+    /// Get how far each node can move between topological layers without adding layers.
+    /// # Returns
+    /// A map from each node to the difference between its latest and earliest possible forward layer.
+    /// # Remarks
+    /// The earliest layer of a node is its index in `get_forward_dependency_topological_layers()`.
+    /// The latest layer is the one from `alap_layers()`.
+    /// Nodes on a longest path have zero slack.
+    pub fn slack(&self) -> HashMap<T, usize> {
+        let earliest = self.forward_depths();
+        return self
+            .alap_layers()
+            .into_iter()
+            .map(|(node, latest)| {
+                let delay = latest - earliest[&node];
+                return (node, delay);
+            })
+            .collect();
+    }
+
+    /// Add a direct dependency and update a forward topological layering in place.
+    /// # Arguments
+    /// * `from` - The node that depends on the other node.
+    /// * `to` - The node that is depended on.
+    /// * `layers` - The result of `get_forward_dependency_topological_layers()` for the graph before the edge is added.
+    /// # Returns
+    /// `Ok(())` if the dependency was added, or the error from `depend_on()` otherwise.
+    /// # Remarks
+    /// Only `from` and its transitive dependents can move, and only to later layers, so nothing else is visited.
+    /// On error, `layers` is left untouched.
+    /// If `layers` is not the current layering of the graph, the result is unspecified.
+    pub fn add_edge_and_update_layers(
+        &mut self,
+        from: T,
+        to: T,
+        layers: &mut Vec<HashSet<T>>,
+    ) -> Result<(), DependencyError> {
+        self.depend_on(from.clone(), to.clone())?;
+
+        let layer_of = |layers: &Vec<HashSet<T>>, node: &T| {
+            return layers.iter().position(|layer| layer.contains(node));
+        };
+        let floor = match layer_of(layers, &to) {
+            Some(depth) => depth + 1,
+            None => {
+                if layers.is_empty() {
+                    layers.push(HashSet::new());
+                }
+                layers[0].insert(to);
+                1
+            }
+        };
+
+        let mut pending = vec![(from, floor)];
+        while let Some((node, floor)) = pending.pop() {
+            match layer_of(layers, &node) {
+                Some(depth) if depth >= floor => continue,
+                Some(depth) => {
+                    layers[depth].remove(&node);
+                }
+                None => {}
+            }
+            if layers.len() <= floor {
+                layers.resize_with(floor + 1, HashSet::new);
+            }
+            for dependent in self.backward_dependencies.get(&node).into_iter().flatten() {
+                pending.push((dependent.clone(), floor + 1));
+            }
+            layers[floor].insert(node);
+        }
+        return Ok(());
+    }
+
+    /// Export the direct dependencies as a JSON adjacency list.
+    /// # Returns
+    /// A JSON object mapping every node to an array of its direct dependencies.
+    /// Nodes without dependencies map to an empty array.
+    /// # Remarks
+    /// Keys and array entries are sorted by their string form so that the output is reproducible.
+    /// Nodes whose string forms coincide produce duplicate keys.
+    pub fn to_json(&self) -> String
+    where
+        T: std::fmt::Display,
+    {
+        let mut entries: Vec<(String, Vec<String>)> = self
+            .nodes
+            .iter()
+            .map(|node| {
+                let mut deps: Vec<String> = self
+                    .forward_dependencies
+                    .get(node)
+                    .into_iter()
+                    .flatten()
+                    .map(|dep| json_string(&dep.to_string()))
+                    .collect();
+                deps.sort();
+                return (json_string(&node.to_string()), deps);
+            })
+            .collect();
+        entries.sort();
+
+        let entries: Vec<String> = entries
+            .into_iter()
+            .map(|(node, deps)| format!("{}:[{}]", node, deps.join(",")))
+            .collect();
+        return format!("{{{}}}", entries.join(","));
+    }
+
+    /// Count the dependents that each leaf would make ready on its own.
+    /// # Returns
+    /// A map from each leaf to the number of its direct dependents whose only dependency is that leaf.
+    /// # Remarks
+    /// These are the nodes that become free of unsatisfied dependencies if the leaf alone is completed.
+    /// The leaf with the highest count is a good greedy choice to schedule next.
+    pub fn unlock_counts(&self) -> HashMap<T, usize> {
+        return self
+            .get_leaves()
+            .into_iter()
+            .map(|leaf| {
+                let count = self
+                    .backward_dependencies
+                    .get(&leaf)
+                    .into_iter()
+                    .flatten()
+                    .filter(|dependent| self.forward_dependencies[*dependent].len() == 1)
+                    .count();
+                return (leaf, count);
+            })
+            .collect();
+    }
+
+    /// Get the pairs of distinct nodes whose names are equal when lowercased.
+    /// # Returns
+    /// Each colliding pair once, with the pair and the list sorted by name.
+    /// # Remarks
+    /// In string-keyed graphs such pairs are usually a typo in one of the dependency declarations.
+    pub fn case_insensitive_collisions(&self) -> Vec<(T, T)>
+    where
+        T: AsRef<str>,
+    {
+        let mut groups: HashMap<String, Vec<&T>> = HashMap::new();
+        for node in &self.nodes {
+            groups
+                .entry(node.as_ref().to_lowercase())
+                .or_default()
+                .push(node);
+        }
+
+        let mut collisions = Vec::new();
+        for mut group in groups.into_values() {
+            group.sort_by(|a, b| a.as_ref().cmp(b.as_ref()));
+            for (i, first) in group.iter().enumerate() {
+                for second in &group[i + 1..] {
+                    collisions.push(((*first).clone(), (*second).clone()));
+                }
+            }
+        }
+        collisions
+            .sort_by(|(a, b), (c, d)| (a.as_ref(), b.as_ref()).cmp(&(c.as_ref(), d.as_ref())));
+        return collisions;
+    }
+
+    /// Get the size of the largest set of nodes where no node depends on another.
+    /// # Returns
+    /// The size of a maximum antichain, which is the most nodes that could ever be processed in parallel.
+    /// # Remarks
+    /// By Dilworth's theorem this equals `min_path_cover()`, and shares its O(V^3) running time.
+    pub fn width(&self) -> usize {
+        return self.min_path_cover();
+    }
+
+    /// Get the forward topological layers with the nodes of each layer ordered for drawing.
+    /// # Returns
+    /// The layers of `get_forward_dependency_topological_layers()`, each ordered to reduce crossings between the edges of neighboring layers.
+    /// # Remarks
+    /// Uses the barycenter heuristic: alternating sweeps move every node towards the mean position of its dependencies, then of its dependents.
+    /// The result reduces crossings but is not guaranteed to minimize them.
+    pub fn ordered_layers(&self) -> Vec<Vec<T>> {
+        const SWEEPS: usize = 4;
+
+        let mut layers: Vec<Vec<T>> = self
+            .get_forward_dependency_topological_layers()
+            .into_iter()
+            .map(|layer| layer.into_iter().collect())
+            .collect();
+        let mut position: HashMap<T, usize> = HashMap::new();
+        for layer in &layers {
+            for (i, node) in layer.iter().enumerate() {
+                position.insert(node.clone(), i);
+            }
+        }
+
+        for sweep in 0..SWEEPS {
+            // even sweeps order by dependencies in earlier layers, odd sweeps by dependents in later layers
+            let (order, neighbors): (Vec<usize>, _) = if sweep % 2 == 0 {
+                ((1..layers.len()).collect(), &self.forward_dependencies)
+            } else {
+                (
+                    (0..layers.len().saturating_sub(1)).rev().collect(),
+                    &self.backward_dependencies,
+                )
+            };
+            for i in order {
+                let mut keyed: Vec<(f64, T)> = layers[i]
+                    .drain(..)
+                    .map(|node| {
+                        let linked: Vec<usize> = neighbors
+                            .get(&node)
+                            .into_iter()
+                            .flatten()
+                            .map(|neighbor| position[neighbor])
+                            .collect();
+                        let barycenter = if linked.is_empty() {
+                            position[&node] as f64
+                        } else {
+                            linked.iter().sum::<usize>() as f64 / linked.len() as f64
+                        };
+                        return (barycenter, node);
+                    })
+                    .collect();
+                keyed.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+                for (j, (_, node)) in keyed.into_iter().enumerate() {
+                    position.insert(node.clone(), j);
+                    layers[i].push(node);
+                }
+            }
+        }
+        return layers;
+    }
+
+    /// Check whether a batch of dependencies could be added, without modifying the graph.
+    /// # Arguments
+    /// * `edges` - The `(from, to)` dependency pairs to try, in order.
+    /// # Returns
+    /// `Ok(())` if adding all of the dependencies in order would succeed.
+    /// `Err(DependencyError::InvalidEdge { index, error })` for the first edge that would fail, as from `load_edges_with_progress()`.
+    /// # Remarks
+    /// The edges are added to a clone of the graph, so cycles formed only by the batch as a whole are detected.
+    pub fn can_add_all(&self, edges: &[(T, T)]) -> Result<(), DependencyError> {
+        let mut trial = self.clone();
+        return trial.load_edges_with_progress(edges, |_, _| {});
+    }
+
+    /// Get the dominator tree of the nodes reachable from a root.
+    /// # Arguments
+    /// * `root` - The node to start from.
+    /// # Returns
+    /// A map from each transitive forward dependency of `root` to its immediate dominator.
+    /// The root itself has no entry, and the map is empty if `root` is not in the graph.
+    /// # Remarks
+    /// A node dominates another if every forward path from `root` to the other node passes through it.
+    /// Since the graph is acyclic, the immediate dominator of a node is the nearest common dominator of its dependents, visited in topological order.
+    pub fn dominators(&self, root: T) -> HashMap<T, T> {
+        let root = match self.nodes.get(&root) {
+            Some(root) => root,
+            None => return HashMap::new(),
+        };
+        let mut reachable: HashSet<&T> = HashSet::from([root]);
+        let mut discovered = vec![root];
+        while let Some(node) = discovered.pop() {
+            for dep in self.forward_dependencies.get(node).into_iter().flatten() {
+                if reachable.insert(dep) {
+                    discovered.push(dep);
+                }
+            }
+        }
+        let dependents = |node: &T| {
+            return self
+                .backward_dependencies
+                .get(node)
+                .into_iter()
+                .flatten()
+                .filter(|dependent| reachable.contains(dependent));
+        };
+
+        let mut remaining: HashMap<&T, usize> = reachable
+            .iter()
+            .map(|node| (*node, dependents(node).count()))
+            .collect();
+        let mut idom: HashMap<&T, &T> = HashMap::new();
+        let mut depth: HashMap<&T, usize> = HashMap::from([(root, 0)]);
+        let mut ready = vec![root];
+        while let Some(node) = ready.pop() {
+            if node != root {
+                let mut dominator = None;
+                for dependent in dependents(node) {
+                    let mut other = dependent;
+                    dominator = Some(match dominator {
+                        None => dependent,
+                        Some(mut current) => {
+                            while current != other {
+                                if depth[current] >= depth[other] {
+                                    current = idom[current];
+                                } else {
+                                    other = idom[other];
+                                }
+                            }
+                            current
+                        }
+                    });
+                }
+                let dominator = dominator.unwrap();
+                depth.insert(node, depth[dominator] + 1);
+                idom.insert(node, dominator);
+            }
+            for dep in self.forward_dependencies.get(node).into_iter().flatten() {
+                let count = remaining.get_mut(dep).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    ready.push(dep);
+                }
+            }
+        }
+        return idom
+            .into_iter()
+            .map(|(node, dominator)| (node.clone(), dominator.clone()))
+            .collect();
+    }
+
+    /// Get the direct dependencies that are part of a diamond.
+    /// # Returns
+    /// Every direct dependency `(from, to)` that lies on some path between a pair of nodes that are connected by at least two distinct paths.
+    /// # Remarks
+    /// Counts paths from every root, saturating at two, in O(R * (V + E)) time where R is the number of roots.
+    /// Any pair joined by two paths can be extended upwards to a root, so only roots need to be tried as path starts.
+    pub fn diamond_edges(&self) -> HashSet<(T, T)> {
+        let mut order = self.leaves_first_order();
+        order.reverse();
+
+        let mut diamond = HashSet::new();
+        for root in self.get_roots() {
+            // number of paths from the root, capped at two
+            let mut paths: HashMap<&T, u8> = HashMap::new();
+            paths.insert(self.nodes.get(&root).unwrap(), 1);
+            for node in &order {
+                let count = match paths.get(node) {
+                    Some(count) => *count,
+                    None => continue,
+                };
+                for dep in self.forward_dependencies.get(*node).into_iter().flatten() {
+                    let total = paths.entry(dep).or_insert(0);
+                    *total = u8::min(*total + count, 2);
+                }
+            }
+
+            // whether a node reaches, or is, a node with multiple paths from the root
+            let mut converges: HashSet<&T> = HashSet::new();
+            for node in order.iter().rev() {
+                if !paths.contains_key(node) {
+                    continue;
+                }
+                let deps = self.forward_dependencies.get(*node).into_iter().flatten();
+                if paths[node] > 1 || deps.clone().any(|dep| converges.contains(dep)) {
+                    converges.insert(node);
+                }
+                for dep in deps.filter(|dep| converges.contains(dep)) {
+                    diamond.insert(((*node).clone(), dep.clone()));
+                }
+            }
+        }
+        return diamond;
+    }
+
+    /// Assign each node a reproducible integer index.
+    /// # Returns
+    /// A map from each node to its position in the sorted list of nodes, from `0` to `V - 1`.
+    /// # Remarks
+    /// The labeling depends only on the set of nodes, not on insertion order or hashing, so it is stable across runs.
+    pub fn stable_labeling(&self) -> HashMap<T, usize>
+    where
+        T: Ord,
+    {
+        let mut nodes: Vec<&T> = self.nodes.iter().collect();
+        nodes.sort();
+        return nodes
+            .into_iter()
+            .enumerate()
+            .map(|(i, node)| (node.clone(), i))
+            .collect();
+    }
+
+    /// Check that no direct dependency joins two nodes of a set.
+    /// # Arguments
+    /// * `nodes` - The set of nodes to check.
+    /// # Returns
+    /// `true` if no member of `nodes` directly depends on another member.
+    /// # Remarks
+    /// Indirect dependencies through nodes outside the set are allowed.
+    /// Together with `is_valid_frontier()` this checks that a set of nodes can be processed as one wave.
+    pub fn is_independent_set(&self, nodes: &HashSet<T>) -> bool {
+        return nodes.iter().all(|node| {
+            return self
+                .forward_dependencies
+                .get(node)
+                .is_none_or(|deps| deps.is_disjoint(nodes));
+        });
+    }
+
+    /// Group the direct dependencies by the node that depends on them.
+    /// # Returns
+    /// Each node with dependencies paired with its sorted direct dependencies.
+    /// Groups are ordered by forward topological layer and then by node, so every node's dependencies have their own groups before it.
+    /// # Remarks
+    /// The result maps directly onto rules of a build file such as a Makefile.
+    pub fn grouped_by_dependent(&self) -> Vec<(T, Vec<T>)>
+    where
+        T: Ord,
+    {
+        let depths = self.forward_depths();
+        let mut groups: Vec<(T, Vec<T>)> = self
+            .forward_dependencies
+            .iter()
+            .map(|(node, deps)| {
+                let mut deps: Vec<T> = deps.iter().cloned().collect();
+                deps.sort();
+                return (node.clone(), deps);
+            })
+            .collect();
+        groups.sort_by(|(a, _), (b, _)| (depths[a], a).cmp(&(depths[b], b)));
+        return groups;
+    }
+
+    /// Get the smallest set of nodes whose transitive dependencies, together with themselves, cover every node.
+    /// # Returns
+    /// The roots of the graph, including any isolated nodes.
+    /// # Remarks
+    /// No node depends on a root, so every root must be in any covering set.
+    /// Conversely, every node of a finite acyclic graph is reachable from some root, so the roots alone suffice.
+    /// The roots are therefore the unique minimal covering set.
+    pub fn minimal_covering_roots(&self) -> HashSet<T> {
+        return self.get_roots();
+    }
+
+    /// Count the complete paths from a root to a leaf that pass through each node.
+    /// # Returns
+    /// A map from each node to the number of root-to-leaf paths through it.
+    /// # Remarks
+    /// Computed as the number of paths from any root to the node times the number of paths from the node to any leaf, in O(V + E) time.
+    /// Path counts grow exponentially in dense graphs, so all arithmetic saturates at `u64::MAX` instead of overflowing.
+    pub fn path_centrality(&self) -> HashMap<T, u64> {
+        let order = self.leaves_first_order();
+
+        let mut paths_out: HashMap<&T, u64> = HashMap::new();
+        for node in &order {
+            let count = match self.forward_dependencies.get(*node) {
+                Some(deps) => deps
+                    .iter()
+                    .fold(0u64, |sum, dep| sum.saturating_add(paths_out[dep])),
+                None => 1,
+            };
+            paths_out.insert(node, count);
+        }
+
+        let mut paths_in: HashMap<&T, u64> = HashMap::new();
+        for node in order.iter().rev() {
+            let count = match self.backward_dependencies.get(*node) {
+                Some(dependents) => dependents.iter().fold(0u64, |sum, dependent| {
+                    sum.saturating_add(paths_in[dependent])
+                }),
+                None => 1,
+            };
+            paths_in.insert(node, count);
+        }
+
+        return order
+            .into_iter()
+            .map(|node| (node.clone(), paths_in[node].saturating_mul(paths_out[node])))
+            .collect();
+    }
+
+    /// Derive the backward topological layer of every node from its forward layer.
+    /// # Arguments
+    /// * `forward` - A map from each node to its index in `get_forward_dependency_topological_layers()`.
+    /// # Returns
+    /// A map from each node to its index in `get_backward_dependency_topological_layers()`.
+    /// # Panics
+    /// If a node of the graph is missing from `forward`.
+    /// # Remarks
+    /// A node's forward layer is always greater than those of its dependencies, so sorting the nodes by descending forward layer gives a reverse topological order.
+    /// A single sweep in that order sets each backward layer to one more than the largest among the node's dependents.
+    /// The sweep still visits every node and direct dependency once, taking O(V + E) time.
+    /// It only saves counting remaining dependents and building the layer sets.
+    pub fn backward_depths_from_forward(&self, forward: &HashMap<T, usize>) -> HashMap<T, usize> {
+        let height = self
+            .nodes
+            .iter()
+            .map(|node| forward[node] + 1)
+            .max()
+            .unwrap_or(0);
+        let mut layers: Vec<Vec<&T>> = vec![Vec::new(); height];
+        for node in &self.nodes {
+            layers[forward[node]].push(node);
+        }
+
+        let mut backward: HashMap<T, usize> = HashMap::new();
+        for node in layers.into_iter().rev().flatten() {
+            let depth = self
+                .backward_dependencies
+                .get(node)
+                .into_iter()
+                .flatten()
+                .map(|dependent| backward[dependent] + 1)
+                .max()
+                .unwrap_or(0);
+            backward.insert(node.clone(), depth);
+        }
+        return backward;
+    }
+
+    /// Get the direct dependencies that would close a short cycle if they were reversed.
+    /// # Arguments
+    /// * `max_distance` - The longest alternative path, in edges, to look for.
+    /// # Returns
+    /// Every direct dependency `(from, to)` for which `from` also reaches `to` through another path of at most `max_distance` edges.
+    /// # Remarks
+    /// Reversing such a dependency would create a cycle of at most `max_distance + 1` nodes, which makes it a fragile spot when refactoring.
+    /// Runs a depth-limited breadth-first search per edge, taking O(E * (V + E)) time in the worst case.
+    pub fn near_cyclic_edges(&self, max_distance: usize) -> Vec<(T, T)> {
+        let mut near_cyclic = Vec::new();
+        for (from, deps) in &self.forward_dependencies {
+            for to in deps {
+                let mut visited: HashSet<&T> = HashSet::from([from]);
+                let mut frontier: Vec<&T> = deps.iter().filter(|dep| *dep != to).collect();
+                visited.extend(frontier.iter().copied());
+                let mut distance = 1;
+                while !frontier.is_empty() && distance < max_distance {
+                    distance += 1;
+                    let mut next = Vec::new();
+                    for node in frontier {
+                        for dep in self.forward_dependencies.get(node).into_iter().flatten() {
+                            if visited.insert(dep) {
+                                next.push(dep);
+                            }
+                        }
+                    }
+                    if visited.contains(to) {
+                        near_cyclic.push((from.clone(), to.clone()));
+                        break;
+                    }
+                    frontier = next;
+                }
+            }
+        }
+        return near_cyclic;
+    }
+
+    /// Encode the graph in a compact binary format.
+    /// # Returns
+    /// The bytes `TPLG`, a format version, the sorted node keys as delta-encoded varints, and the direct dependencies as varint pairs of node indices.
+    /// # Panics
+    /// If a node does not convert to `u64`, such as a negative signed integer.
+    /// # Remarks
+    /// Only the forward dependencies are written; `from_bytes()` rebuilds the backward dependencies.
+    /// The output depends only on the nodes and dependencies, so equal graphs encode to equal bytes.
+    pub fn to_bytes(&self) -> Vec<u8>
+    where
+        T: Copy + TryInto<u64>,
+    {
+        let mut keys: Vec<(u64, &T)> = self
+            .nodes
+            .iter()
+            .map(|node| match (*node).try_into() {
+                Ok(key) => (key, node),
+                Err(_) => panic!("node does not fit in a u64 key"),
+            })
+            .collect();
+        keys.sort_unstable_by_key(|(key, _)| *key);
+        let index: HashMap<&T, usize> = keys
+            .iter()
+            .enumerate()
+            .map(|(i, (_, node))| (*node, i))
+            .collect();
+        let mut edges: Vec<(usize, usize)> = Vec::new();
+        for (from, deps) in &self.forward_dependencies {
+            for to in deps {
+                edges.push((index[from], index[to]));
+            }
+        }
+        edges.sort_unstable();
+
+        let mut bytes = Vec::from(BINARY_MAGIC);
+        bytes.push(BINARY_VERSION);
+        write_varint(&mut bytes, keys.len() as u64);
+        let mut previous = 0;
+        for (key, _) in keys {
+            write_varint(&mut bytes, key - previous);
+            previous = key;
+        }
+        write_varint(&mut bytes, edges.len() as u64);
+        for (from, to) in edges {
+            write_varint(&mut bytes, from as u64);
+            write_varint(&mut bytes, to as u64);
+        }
+        return bytes;
+    }
+
+    /// Decode a graph written by `to_bytes()`.
+    /// # Arguments
+    /// * `data` - The encoded graph.
+    /// # Returns
+    /// The decoded graph, or a `DecodeError` describing the first problem found.
+    /// # Remarks
+    /// Only the canonical form written by `to_bytes()` is accepted, so repeated nodes or dependencies are rejected rather than merged.
+    /// Dependencies are inserted directly and checked for cycles in a single pass at the end, taking O(V + E) time.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, DecodeError>
+    where
+        T: Copy + TryFrom<u64>,
+    {
+        let header = BINARY_MAGIC.len() + 1;
+        if data.len() < header
+            || data[..BINARY_MAGIC.len()] != BINARY_MAGIC
+            || data[header - 1] != BINARY_VERSION
+        {
+            return Err(DecodeError::InvalidHeader);
+        }
+        let mut offset = header;
+
+        let count = read_varint(data, &mut offset)?;
+        let mut nodes: Vec<T> = Vec::new();
+        let mut key: u64 = 0;
+        for _ in 0..count {
+            let delta = read_varint(data, &mut offset)?;
+            // keys are strictly increasing, so only the first may be zero
+            if delta == 0 && !nodes.is_empty() {
+                return Err(DecodeError::InvalidNode);
+            }
+            key = key.checked_add(delta).ok_or(DecodeError::InvalidNode)?;
+            nodes.push(T::try_from(key).map_err(|_| DecodeError::InvalidNode)?);
+        }
+
+        let mut graph = AcyclicDependencyGraph::with_capacity(nodes.len());
+        graph.nodes.extend(nodes.iter().copied());
+        let edges = read_varint(data, &mut offset)?;
+        for _ in 0..edges {
+            let mut node = || {
+                let index = read_varint(data, &mut offset)?;
+                return nodes
+                    .get(index as usize)
+                    .copied()
+                    .ok_or(DecodeError::InvalidNode);
+            };
+            let from = node()?;
+            let to = node()?;
+            if from == to {
+                return Err(DecodeError::InvalidEdge(DependencyError::SelfReference));
+            }
+            // cycles are checked once all edges are in, instead of searching on every insert
+            if !graph
+                .forward_dependencies
+                .entry(from)
+                .or_default()
+                .insert(to)
+            {
+                return Err(DecodeError::DuplicateEdge);
+            }
+            graph
+                .backward_dependencies
+                .entry(to)
+                .or_default()
+                .insert(from);
+        }
+        if offset != data.len() {
+            return Err(DecodeError::TrailingBytes);
+        }
+
+        // nodes on a cycle never run out of dependencies, so they are left out of every layer
+        let layers = graph.peel_layers(&graph.forward_dependencies, &graph.backward_dependencies);
+        if layers.iter().map(HashSet::len).sum::<usize>() != graph.nodes.len() {
+            return Err(DecodeError::InvalidEdge(
+                DependencyError::CircularDependency,
+            ));
+        }
+        return Ok(graph);
+    }
+
+    /// Split the graph into its weakly connected components.
+    /// # Returns
+    /// One graph per weakly connected component, each holding the component's nodes and all of their dependencies.
+    /// # Remarks
+    /// No dependency crosses between components, so each component can be processed independently.
+    /// Components are in no particular order.
+    pub fn split_components(&self) -> Vec<AcyclicDependencyGraph<T>> {
+        let mut assigned: HashSet<&T> = HashSet::new();
+        let mut components = Vec::new();
+        for start in &self.nodes {
+            if assigned.contains(start) {
+                continue;
+            }
+            let members = self.weakly_connected_component(start);
+            let mut component = AcyclicDependencyGraph::with_capacity(members.len());
+            for node in &members {
+                component.nodes.insert((*node).clone());
+                if let Some(deps) = self.forward_dependencies.get(*node) {
+                    component
+                        .forward_dependencies
+                        .insert((*node).clone(), deps.clone());
+                }
+                if let Some(dependents) = self.backward_dependencies.get(*node) {
+                    component
+                        .backward_dependencies
+                        .insert((*node).clone(), dependents.clone());
+                }
+            }
+            assigned.extend(members);
+            components.push(component);
+        }
+        return components;
+    }
+
+    /// Remove a single direct dependency.
+    /// # Arguments
+    /// * `from` - The node that depends on the other node.
+    /// * `to` - The node that is depended on.
+    /// # Returns
+    /// `true` if the dependency existed and was removed.
+    /// # Remarks
+    /// Both nodes stay in the graph.
+    fn remove_edge(&mut self, from: &T, to: &T) -> bool {
+        let removed = match self.forward_dependencies.get_mut(from) {
+            Some(deps) => deps.remove(to),
+            None => false,
+        };
+        if !removed {
+            return false;
+        }
+        if self.forward_dependencies[from].is_empty() {
+            self.forward_dependencies.remove(from);
+        }
+        let dependents = self.backward_dependencies.get_mut(to).unwrap();
+        dependents.remove(from);
+        if dependents.is_empty() {
+            self.backward_dependencies.remove(to);
+        }
+
+        #[cfg(debug_assertions)]
+        for node in [from, to] {
+            self.assert_consistent_at(node);
+        }
+        return true;
+    }
+
+    /// Get the changes that turn the direct dependencies of this graph into those of another.
+    /// # Arguments
+    /// * `other` - The graph whose direct dependencies should be reached.
+    /// # Returns
+    /// A removal for every dependency missing from `other`, followed by an addition for every dependency missing from this graph.
+    /// # Remarks
+    /// Only dependencies are compared; nodes without any dependencies or dependents are not part of the patch.
+    /// Removals come first so that replaying the patch with `apply_edge_patch()` never passes through a cycle.
+    pub fn edge_patch_to(&self, other: &AcyclicDependencyGraph<T>) -> Vec<EdgeOp<T>> {
+        let missing = |from: &AcyclicDependencyGraph<T>, to: &AcyclicDependencyGraph<T>| {
+            let mut edges = Vec::new();
+            for (node, deps) in &from.forward_dependencies {
+                for dep in deps {
+                    if !to.contains_edge(node, dep) {
+                        edges.push((node.clone(), dep.clone()));
+                    }
+                }
+            }
+            return edges;
+        };
+        let removals = missing(self, other)
+            .into_iter()
+            .map(|(from, to)| EdgeOp::Remove(from, to));
+        let additions = missing(other, self)
+            .into_iter()
+            .map(|(from, to)| EdgeOp::Add(from, to));
+        return removals.chain(additions).collect();
+    }
+
+    /// Apply changes to the direct dependencies in order.
+    /// # Arguments
+    /// * `patch` - The changes to apply, such as those from `edge_patch_to()`.
+    /// # Returns
+    /// `Ok(())` if every change was applied.
+    /// `Err(DependencyError::InvalidEdge { index, error })` for the first addition that could not be made.
+    /// # Remarks
+    /// Removing a dependency that does not exist does nothing.
+    /// Changes before the failing one remain applied.
+    pub fn apply_edge_patch(&mut self, patch: &[EdgeOp<T>]) -> Result<(), DependencyError> {
+        for (index, op) in patch.iter().enumerate() {
+            match op {
+                EdgeOp::Add(from, to) => {
+                    if let Err(error) = self.depend_on(from.clone(), to.clone()) {
+                        return Err(DependencyError::InvalidEdge {
+                            index,
+                            error: Box::new(error),
+                        });
+                    }
+                }
+                EdgeOp::Remove(from, to) => {
+                    self.remove_edge(from, to);
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    /// Add a dependency between two nodes unless it would make the graph too deep.
+    /// # Arguments
+    /// * `from` - The node that depends on the other node.
+    /// * `to` - The node that is depended on.
+    /// * `max_depth` - The largest forward topological layer index any node may have.
+    /// # Returns
+    /// `Ok(())` if the dependency was added or already existed.
+    /// `Err(DependencyError::DepthExceeded)` if some node would end up past layer `max_depth` of `get_forward_dependency_topological_layers()`.
+    /// Otherwise the same errors as `depend_on()`.
+    /// # Remarks
+    /// The deepest node after adding the dependency is the root farthest above `from`, which sits one layer above the longest chain below `to`.
+    /// Computing both takes O(V + E) time.
+    pub fn depend_on_limited(
+        &mut self,
+        from: T,
+        to: T,
+        max_depth: usize,
+    ) -> Result<(), DependencyError> {
+        if from == to {
+            return Err(DependencyError::SelfReference);
+        }
+        if self.depends_on(&to, &from) {
+            return Err(DependencyError::CircularDependency);
+        }
+        if !self.contains_edge(&from, &to) {
+            let forward = self.forward_depths();
+            let below = forward.get(&to).copied().unwrap_or(0);
+            let above = if forward.contains_key(&from) {
+                self.backward_depths_from_forward(&forward)[&from]
+            } else {
+                0
+            };
+            if above + 1 + below > max_depth {
+                return Err(DependencyError::DepthExceeded);
+            }
+        }
+        return self.depend_on(from, to);
+    }
+
+    /// Get the nodes that a node neither is nor depends on.
+    /// # Arguments
+    /// * `node` - The node whose requirements to exclude.
+    /// # Returns
+    /// Every node except `node` and its transitive forward dependencies.
+    /// # Remarks
+    /// These are the parts of the graph that building `node` would never touch.
+    pub fn not_required_by(&self, node: T) -> HashSet<T> {
+        let mut required = self.get_forward_dependencies(&node);
+        required.insert(node);
+        return self.nodes.difference(&required).cloned().collect();
+    }
+
+    /// Get how many shortest paths between other nodes pass through each node.
+    /// # Returns
+    /// A map from every node to the sum, over all ordered pairs of other nodes, of the fraction of shortest forward paths between them that pass through it.
+    /// # Remarks
+    /// High scores mark pivotal intermediate nodes; roots and leaves always score zero.
+    /// Uses Brandes' algorithm in O(V * E) time, sharing its computation with `edge_betweenness()`.
+    pub fn node_betweenness(&self) -> HashMap<T, f64> {
+        return self.shortest_path_betweenness().0;
+    }
+
+    /// Get an order in which every node comes after its dependencies, choosing among ready nodes by priority.
+    /// # Arguments
+    /// * `cmp` - Orders the ready nodes; whenever several nodes have all of their dependencies placed, the least one is placed next.
+    /// # Returns
+    /// Every node, each after all of its dependencies.
+    /// # Remarks
+    /// Runs Kahn's algorithm with the ready nodes in a heap, taking O(V log V + E log V) time.
+    /// The nodes are sorted by `cmp` once up front and the heap holds their ranks, so `cmp` is called O(V log V) times.
+    /// A total order gives a deterministic result; the order among nodes that `cmp` considers equal is not guaranteed.
+    pub fn topological_sort_by<F: FnMut(&T, &T) -> std::cmp::Ordering>(
+        &self,
+        mut cmp: F,
+    ) -> Vec<T> {
+        let mut ranked: Vec<&T> = self.nodes.iter().collect();
+        ranked.sort_by(|a, b| cmp(a, b));
+        let rank: HashMap<&T, usize> = ranked
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (*node, i))
+            .collect();
+
+        let mut remaining: HashMap<&T, usize> = self
+            .forward_dependencies
+            .iter()
+            .map(|(node, deps)| (node, deps.len()))
+            .collect();
+        // reversed ranks make the max-heap pop the least ready node first
+        let mut ready: BinaryHeap<std::cmp::Reverse<usize>> = self
+            .nodes
+            .iter()
+            .filter(|node| !remaining.contains_key(node))
+            .map(|node| std::cmp::Reverse(rank[node]))
+            .collect();
+
+        let mut order = Vec::with_capacity(self.nodes.len());
+        while let Some(std::cmp::Reverse(i)) = ready.pop() {
+            let node = ranked[i];
+            order.push(node.clone());
+            for dependent in self.backward_dependencies.get(node).into_iter().flatten() {
+                let count = remaining.get_mut(dependent).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    ready.push(std::cmp::Reverse(rank[dependent]));
+                }
+            }
+        }
+        return order;
+    }
+
+    /// Remove dependencies that leave the graph in an inconsistent state.
+    /// # Returns
+    /// The number of distinct dependencies removed.
+    /// # Remarks
+    /// A dependency is removed if either endpoint is not a node of the graph, or if it is recorded in only one of the forward and backward dependency maps.
+    /// Empty dependency sets are dropped as well.
+    /// The graph's own methods keep it consistent, so this only guards against bugs.
+    pub fn repair(&mut self) -> usize {
+        let valid = |from: &T, to: &T| {
+            return self.nodes.contains(from)
+                && self.nodes.contains(to)
+                && self
+                    .forward_dependencies
+                    .get(from)
+                    .is_some_and(|deps| deps.contains(to))
+                && self
+                    .backward_dependencies
+                    .get(to)
+                    .is_some_and(|dependents| dependents.contains(from));
+        };
+        let mut invalid: HashSet<(T, T)> = HashSet::new();
+        for (from, deps) in &self.forward_dependencies {
+            for to in deps.iter().filter(|to| !valid(from, to)) {
+                invalid.insert((from.clone(), to.clone()));
+            }
+        }
+        for (to, dependents) in &self.backward_dependencies {
+            for from in dependents.iter().filter(|from| !valid(from, to)) {
+                invalid.insert((from.clone(), to.clone()));
+            }
+        }
+
+        for (from, to) in &invalid {
+            if let Some(deps) = self.forward_dependencies.get_mut(from) {
+                deps.remove(to);
+            }
+            if let Some(dependents) = self.backward_dependencies.get_mut(to) {
+                dependents.remove(from);
+            }
+        }
+        self.forward_dependencies.retain(|_, deps| !deps.is_empty());
+        self.backward_dependencies
+            .retain(|_, dependents| !dependents.is_empty());
+
+        #[cfg(debug_assertions)]
+        self.assert_consistent();
+        return invalid.len();
+    }
+
+    /// Assign the nodes to time slots for a fixed number of parallel workers.
+    /// # Arguments
+    /// * `workers` - The most nodes that can be processed in the same time slot.
+    /// # Returns
+    /// The time slots in order, each holding at most `workers` nodes whose dependencies are all in earlier slots.
+    /// # Panics
+    /// If `workers` is zero and the graph is not empty.
+    /// # Remarks
+    /// Uses greedy list scheduling: each slot takes the ready nodes with the longest chains of dependents waiting on them.
+    /// The result is not always the shortest possible schedule, but with unlimited workers it matches `get_forward_dependency_topological_layers()`.
+    pub fn schedule_with_workers(&self, workers: usize) -> Vec<Vec<T>> {
+        assert!(
+            workers > 0 || self.nodes.is_empty(),
+            "at least one worker is needed"
+        );
+
+        // the longest chain of dependents above each node
+        let order = self.leaves_first_order();
+        let mut above: HashMap<&T, usize> = HashMap::new();
+        for node in order.iter().rev() {
+            let length = self
+                .backward_dependencies
+                .get(*node)
+                .into_iter()
+                .flatten()
+                .map(|dependent| above[dependent] + 1)
+                .max()
+                .unwrap_or(0);
+            above.insert(node, length);
+        }
+
+        let mut remaining: HashMap<&T, usize> = self
+            .forward_dependencies
+            .iter()
+            .map(|(node, deps)| (node, deps.len()))
+            .collect();
+        let mut ready: Vec<&T> = self
+            .nodes
+            .iter()
+            .filter(|node| !remaining.contains_key(node))
+            .collect();
+        let mut slots = Vec::new();
+        while !ready.is_empty() {
+            ready.sort_by_key(|node| std::cmp::Reverse(above[node]));
+            let slot: Vec<&T> = ready.drain(..usize::min(workers, ready.len())).collect();
+            for node in &slot {
+                for dependent in self.backward_dependencies.get(*node).into_iter().flatten() {
+                    let count = remaining.get_mut(dependent).unwrap();
+                    *count -= 1;
+                    if *count == 0 {
+                        ready.push(dependent);
+                    }
+                }
+            }
+            slots.push(slot.into_iter().cloned().collect());
+        }
+        return slots;
+    }
+
+    /// Get the latest forward topological layer each node can occupy without adding layers.
+    /// # Returns
+    /// A map from each node to the highest layer index it can be placed in while staying below all of its dependents and within the graph's height.
+    /// # Remarks
+    /// This is the as-late-as-possible placement, in contrast to the as-soon-as-possible placement of `get_forward_dependency_topological_layers()`.
+    /// A node's latest layer is the graph's height less one more than its index in `get_backward_dependency_topological_layers()`.
+    pub fn alap_layers(&self) -> HashMap<T, usize> {
+        let layers = self.get_backward_dependency_topological_layers();
+        let height = layers.len();
+        let mut latest = HashMap::new();
+        for (depth, layer) in layers.into_iter().enumerate() {
+            for node in layer {
+                latest.insert(node, height - 1 - depth);
+            }
+        }
+        return latest;
+    }
+
+    /// Get the direct dependencies along a longest chain in the graph.
+    /// # Returns
+    /// The consecutive pairs `(from, to)` of the path from `longest_path()`.
+    /// The set is empty if the graph has no dependencies.
+    /// # Remarks
+    /// When several paths are equally long the edges of an arbitrary one are returned.
+    pub fn longest_path_edges(&self) -> HashSet<(T, T)> {
+        return self
+            .longest_path()
+            .windows(2)
+            .map(|pair| (pair[0].clone(), pair[1].clone()))
+            .collect();
+    }
+
+    /// Add a dependency between two nodes only if it keeps every node at no more than one direct dependency.
+    /// # Arguments
+    /// * `from` - The node that depends on the other node.
+    /// * `to` - The node that is depended on.
+    /// # Returns
+    /// `Ok(())` if the dependency was added or already existed.
+    /// `Err(DependencyError::NotATree)` if `from` already depends directly on another node.
+    /// Otherwise the same errors as `depend_on()`.
+    /// # Remarks
+    /// A graph built only through this method is a forest in which each node's single dependency is its parent.
+    pub fn depend_on_tree(&mut self, from: T, to: T) -> Result<(), DependencyError> {
+        if from == to {
+            return Err(DependencyError::SelfReference);
+        }
+        if self.depends_on(&to, &from) {
+            return Err(DependencyError::CircularDependency);
+        }
+        if self
+            .forward_dependencies
+            .get(&from)
+            .is_some_and(|deps| !deps.contains(&to))
+        {
+            return Err(DependencyError::NotATree);
+        }
+        return self.depend_on(from, to);
+    }
+
+    /// Remove a direct dependency and report which transitive dependencies were lost.
+    /// # Arguments
+    /// * `from` - The node that depends on the other node.
+    /// * `to` - The node that is depended on.
+    /// # Returns
+    /// The nodes that `from` transitively depended on before the removal but no longer does.
+    /// The set is empty if the dependency did not exist or if `to` is still reachable another way.
+    /// # Remarks
+    /// Both nodes stay in the graph.
+    /// Only `from` is reported; each of its dependents lost a subset of the same nodes.
+    pub fn remove_edge_tracked(&mut self, from: T, to: T) -> HashSet<T> {
+        if !self.contains_edge(&from, &to) {
+            return HashSet::new();
+        }
+        let before = self.get_forward_dependencies(&from);
+        self.remove_edge(&from, &to);
+        let after = self.get_forward_dependencies(&from);
+        return before.difference(&after).cloned().collect();
+    }
+
+    /// Get the nodes that only pass a single dependency through to a single dependent.
+    /// # Returns
+    /// Every node with exactly one direct dependency and exactly one direct dependent.
+    /// # Remarks
+    /// Removing such a node and making its dependent depend directly on its dependency keeps every other node's reachability unchanged.
+    /// This makes them candidates for collapsing chains.
+    pub fn passthrough_nodes(&self) -> HashSet<T> {
+        return self
+            .forward_dependencies
+            .iter()
+            .filter(|(node, deps)| {
+                return deps.len() == 1
+                    && self
+                        .backward_dependencies
+                        .get(*node)
+                        .is_some_and(|dependents| dependents.len() == 1);
+            })
+            .map(|(node, _)| node.clone())
+            .collect();
+    }
+
+    /// Count the nodes with each number of direct dependencies.
+    /// # Returns
+    /// A map from each out-degree that occurs to the number of nodes with that many direct dependencies, in ascending order of out-degree.
+    /// # Remarks
+    /// Mass at high out-degrees marks a bushy graph, while mass at one marks a chain-like graph.
+    pub fn out_degree_histogram(&self) -> BTreeMap<usize, usize> {
+        let mut histogram = BTreeMap::new();
+        for node in &self.nodes {
+            let degree = self
+                .forward_dependencies
+                .get(node)
+                .map_or(0, |deps| deps.len());
+            *histogram.entry(degree).or_insert(0) += 1;
+        }
+        return histogram;
+    }
+
+    /// Check that the direct dependencies are exactly a given set.
+    /// # Arguments
+    /// * `edges` - The expected `(from, to)` dependency pairs, in any order.
+    /// # Returns
+    /// `true` if every pair in `edges` is a direct dependency and there are no other direct dependencies.
+    /// # Remarks
+    /// Repeated pairs in `edges` count once, and nodes without dependencies or dependents are not compared.
+    pub fn has_exactly_edges(&self, edges: &[(T, T)]) -> bool {
+        let expected: HashSet<(&T, &T)> = edges.iter().map(|(from, to)| (from, to)).collect();
+        let count: usize = self
+            .forward_dependencies
+            .values()
+            .map(|deps| deps.len())
+            .sum();
+        return expected.len() == count
+            && expected
+                .iter()
+                .all(|(from, to)| self.contains_edge(from, to));
+    }
+
+    /// Combine a node's depth and number of dependents into a single score.
+    /// # Arguments
+    /// * `node` - The node to score.
+    /// * `depth_weight` - The weight of the node's index in `get_forward_dependency_topological_layers()`.
+    /// * `dependent_weight` - The weight of the node's `transitive_dependent_count()`.
+    /// # Returns
+    /// `depth_weight * depth + dependent_weight * dependents`, which is zero for a node not in the graph.
+    pub fn impact_score(&self, node: T, depth_weight: f64, dependent_weight: f64) -> f64 {
+        let depth = self.forward_depths().get(&node).copied().unwrap_or(0);
+        let dependents = self.transitive_dependent_count(node);
+        return depth_weight * depth as f64 + dependent_weight * dependents as f64;
+    }
+
+    /// Build a graph from numbered dependency declarations.
+    /// # Arguments
+    /// * `lines` - The `(line_number, from, to)` declarations, in input order.
+    /// # Returns
+    /// The graph with every dependency added.
+    /// `Err((line_number, error))` for the first declaration that could not be added.
+    /// # Remarks
+    /// Line numbers are only used for reporting, so they may start anywhere and skip values such as blank or comment lines.
+    pub fn build_from_lines<I: IntoIterator<Item = (usize, T, T)>>(
+        lines: I,
+    ) -> Result<AcyclicDependencyGraph<T>, (usize, DependencyError)> {
+        let mut graph = AcyclicDependencyGraph::new();
+        for (line, from, to) in lines {
+            graph.depend_on(from, to).map_err(|error| (line, error))?;
+        }
+        return Ok(graph);
+    }
+}
+
+impl<T> Default for AcyclicDependencyGraph<T>
+where
+    T: Eq + Hash + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A view into the direct dependencies of a single node.
+/// Created by `AcyclicDependencyGraph::dependency_entry()`.
+/// The entry is occupied when the node has direct dependencies and vacant otherwise.
+pub struct DependencyEntry<'a, T> {
+    graph: &'a mut AcyclicDependencyGraph<T>,
+    node: T,
+}
+
+impl<T> DependencyEntry<'_, T>
+where
+    T: Eq + Hash + Clone,
+{
+    /// Get the node this entry refers to.
+    pub fn key(&self) -> &T {
+        &self.node
+    }
+
+    /// Get the direct dependencies of the node.
+    /// # Returns
+    /// `Some` set of direct dependencies if the entry is occupied.
+    /// `None` if the entry is vacant.
+    pub fn dependencies(&self) -> Option<&HashSet<T>> {
+        self.graph.forward_dependencies.get(&self.node)
+    }
+
+    /// Add a dependency from the node of this entry.
+    /// # Arguments
+    /// * `to` - The node to depend on.
+    /// # Returns
+    /// The same result as `AcyclicDependencyGraph::depend_on()`.
+    pub fn depend_on(&mut self, to: T) -> Result<(), DependencyError> {
+        self.graph.depend_on(self.node.clone(), to)
+    }
+
+    /// Add a dependency only if the entry is vacant.
+    /// # Arguments
+    /// * `to` - The node to depend on.
+    /// # Returns
+    /// `Ok(self)` for further chaining if the dependency was added or the entry was occupied.
+    /// `Err(DependencyError)` if the dependency could not be added.
+    pub fn or_depend_on(mut self, to: T) -> Result<Self, DependencyError> {
+        if self.dependencies().is_none() {
+            self.depend_on(to)?;
+        }
+        return Ok(self);
+    }
+
+    /// Modify the entry only if it is occupied.
+    /// # Arguments
+    /// * `f` - Called with the entry if the node has direct dependencies.
+    /// # Returns
+    /// `Ok(self)` for further chaining if `f` succeeded or was not called.
+    /// `Err(DependencyError)` if `f` failed.
+    pub fn and_modify<F>(mut self, f: F) -> Result<Self, DependencyError>
+    where
+        F: FnOnce(&mut Self) -> Result<(), DependencyError>,
+    {
+        if self.dependencies().is_some() {
+            f(&mut self)?;
+        }
+        return Ok(self);
+    }
+}
+
+/// Graphs are equal when they have the same nodes and the same direct dependencies.
+impl<T> PartialEq for AcyclicDependencyGraph<T>
+where
+    T: Eq + Hash,
+{
+    fn eq(&self, other: &Self) -> bool {
+        // the backward dependencies mirror the forward dependencies so they need not be compared
+        self.nodes == other.nodes && self.forward_dependencies == other.forward_dependencies
+    }
+}
+
+impl<T> Eq for AcyclicDependencyGraph<T> where T: Eq + Hash {}
+
+/// Hashes the canonical (sorted) nodes and direct dependencies.
+/// Sorting makes the hash independent of the iteration order of the internal sets, which keeps it consistent with `Eq`.
+impl<T> Hash for AcyclicDependencyGraph<T>
+where
+    T: Eq + Hash + Clone + Ord,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.canonical_form().hash(state);
+    }
+}
+
+/// A precomputed table of which nodes each node depends on.
+/// Created by `AcyclicDependencyGraph::build_reachability_index()`.
+/// # Fields
+/// * `index` - The row and column of each node in the table.
+/// * `words` - The number of 64-bit words in each row.
+/// * `bits` - The rows of the table, one bit per node.
+pub struct ReachabilityIndex<T> {
+    index: HashMap<T, usize>,
+    words: usize,
+    bits: Vec<u64>,
+}
+
+impl<T> ReachabilityIndex<T>
+where
+    T: Eq + Hash,
+{
+    /// Check if one node depends on another.
+    /// # Arguments
+    /// * `from` - The node that depends on the other node.
+    /// * `to` - The node that is depended on.
+    /// # Returns
+    /// The same answer as `AcyclicDependencyGraph::depends_on()` at the time the index was built.
+    pub fn reaches(&self, from: &T, to: &T) -> bool {
+        match (self.index.get(from), self.index.get(to)) {
+            (Some(i), Some(j)) => self.bits[i * self.words + j / 64] & (1 << (j % 64)) != 0,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cake_graph() -> AcyclicDependencyGraph<&'static str> {
+        let mut graph = AcyclicDependencyGraph::new();
+        graph.depend_on("cake", "eggs").unwrap();
+        graph.depend_on("cake", "flour").unwrap();
+        graph.depend_on("eggs", "chickens").unwrap();
+        graph.depend_on("flour", "grain").unwrap();
+        graph.depend_on("chickens", "grain").unwrap();
+        graph.depend_on("grain", "soil").unwrap();
+        graph.depend_on("grain", "water").unwrap();
+        graph.depend_on("chickens", "water").unwrap();
+        return graph;
+    }
+
+    #[test]
+    fn empty_graph_is_empty() {
+        let graph: AcyclicDependencyGraph<&str> = AcyclicDependencyGraph::new();
+        assert!(graph.is_empty());
+    }
+
+    #[test]
+    fn non_empty_graph_is_not_empty() {
+        let mut graph = AcyclicDependencyGraph::new();
+        graph.depend_on("a", "b").unwrap();
+        assert!(!graph.is_empty());
+    }
+
+    #[test]
+    fn self_referential_dependencies_detected() {
+        let mut graph = AcyclicDependencyGraph::new();
+        assert!(graph.depend_on("a", "a").is_err());
+    }
+
+    #[test]
+    fn circular_dependencies_detected() {
+        let mut graph = AcyclicDependencyGraph::new();
+        graph.depend_on("a", "b").unwrap();
+        graph.depend_on("b", "c").unwrap();
+        assert!(graph.depend_on("c", "a").is_err());
+    }
+
+    #[test]
+    fn simple_topological_sort_forward() {
+        let mut graph = AcyclicDependencyGraph::new();
+        graph.depend_on("cake", "eggs").unwrap();
+        graph.depend_on("cake", "flour").unwrap();
+        graph.depend_on("eggs", "chickens").unwrap();
+        graph.depend_on("flour", "grain").unwrap();
+        graph.depend_on("chickens", "grain").unwrap();
+        graph.depend_on("grain", "soil").unwrap();
+        graph.depend_on("grain", "water").unwrap();
+        graph.depend_on("chickens", "water").unwrap();
+
+        let layers = graph.get_forward_dependency_topological_layers();
+        println!("layers: {:?}", layers);
+
+        // check forward and backward direct dependencies
+        // note: we cannot guarantee any particular topological ordering so checking the direct dependencies is a second-best option
+        let fwd_bckwd_check = |node: &str, expected_fwd: Vec<&str>, expected_bwd: Vec<&str>| {
+            let fwd = graph.get_forward_dependencies(&node);
+            let bwd = graph.get_backward_dependencies(&node);
+
+            assert_eq!(fwd.len(), expected_fwd.len());
+            assert_eq!(bwd.len(), expected_bwd.len());
+            for expected_node in expected_fwd {
+                assert!(fwd.contains(&expected_node));
+            }
+            for expected_node in expected_bwd {
+                println!("expected_node: {}", &expected_node);
+                assert!(bwd.contains(&expected_node));
+            }
+        };
+        fwd_bckwd_check(
+            "cake",
+            vec!["eggs", "grain", "soil", "chickens", "water", "flour"],
+            vec![],
+        );
+        fwd_bckwd_check(
+            "eggs",
+            vec!["chickens", "grain", "soil", "water"],
+            vec!["cake"],
+        );
+        fwd_bckwd_check("flour", vec!["grain", "soil", "water"], vec!["cake"]);
+        fwd_bckwd_check(
+            "chickens",
+            vec!["grain", "soil", "water"],
             vec!["cake", "eggs"],
         );
-        fwd_bckwd_check(
-            "grain",
-            vec!["water", "soil"],
-            vec!["cake", "eggs", "flour", "chickens"],
+        fwd_bckwd_check(
+            "grain",
+            vec!["water", "soil"],
+            vec!["cake", "eggs", "flour", "chickens"],
+        );
+        fwd_bckwd_check(
+            "soil",
+            vec![],
+            vec!["grain", "eggs", "flour", "cake", "chickens"],
+        );
+        fwd_bckwd_check(
+            "water",
+            vec![],
+            vec!["grain", "eggs", "flour", "cake", "chickens"],
+        );
+    }
+
+    #[test]
+    fn load_edges_with_progress_reports_progress() {
+        let edges: Vec<(usize, usize)> = (0..250).map(|i| (i + 1, i)).collect();
+        let mut graph = AcyclicDependencyGraph::new();
+        let mut calls = Vec::new();
+        graph
+            .load_edges_with_progress(&edges, |done, total| calls.push((done, total)))
+            .unwrap();
+
+        // 250 edges report every 2 edges
+        assert_eq!(calls.len(), 125);
+        assert_eq!(calls.last(), Some(&(250, 250)));
+        assert!(calls.windows(2).all(|w| w[0].0 < w[1].0));
+        assert!(graph.depends_on(&250, &0));
+        assert_eq!(graph.get_leaves(), HashSet::from([0]));
+        assert_eq!(graph.get_roots(), HashSet::from([250]));
+    }
+
+    #[test]
+    fn load_edges_with_progress_reports_failing_edge() {
+        let edges = [("a", "b"), ("b", "c"), ("c", "a"), ("c", "d")];
+        let mut graph = AcyclicDependencyGraph::new();
+        let mut calls = 0;
+        let result = graph.load_edges_with_progress(&edges, |_, _| calls += 1);
+        assert!(matches!(
+            result,
+            Err(DependencyError::InvalidEdge { index: 2, ref error })
+                if matches!(**error, DependencyError::CircularDependency)
+        ));
+        assert_eq!(calls, 2);
+        assert!(graph.depends_on(&"a", &"c"));
+        assert!(!graph.depends_on(&"c", &"d"));
+    }
+
+    #[test]
+    fn min_vertex_cut_finds_bottleneck() {
+        let graph = cake_graph();
+        assert_eq!(
+            graph.min_vertex_cut("cake", "soil"),
+            HashSet::from(["grain"])
+        );
+        assert_eq!(
+            graph.min_vertex_cut("cake", "chickens"),
+            HashSet::from(["eggs"])
+        );
+        assert_eq!(graph.min_vertex_cut("cake", "water").len(), 2);
+        assert!(graph.min_vertex_cut("soil", "cake").is_empty());
+        assert!(graph.min_vertex_cut("grain", "soil").is_empty());
+    }
+
+    #[test]
+    fn relabeled_graphs_are_isomorphic() {
+        let graph = cake_graph();
+        let mut relabeled = AcyclicDependencyGraph::new();
+        for (from, to) in [
+            (1, 2),
+            (1, 3),
+            (2, 4),
+            (3, 5),
+            (4, 5),
+            (5, 6),
+            (5, 7),
+            (4, 7),
+        ] {
+            relabeled.depend_on(from, to).unwrap();
+        }
+        assert!(graph.is_isomorphic_to(&relabeled));
+        assert!(relabeled.is_isomorphic_to(&graph));
+
+        relabeled.depend_on(1, 6).unwrap();
+        assert!(!graph.is_isomorphic_to(&relabeled));
+    }
+
+    #[test]
+    fn differently_shaped_graphs_are_not_isomorphic() {
+        let mut chain = AcyclicDependencyGraph::new();
+        chain.depend_on("a", "b").unwrap();
+        chain.depend_on("b", "c").unwrap();
+        let mut fork = AcyclicDependencyGraph::new();
+        fork.depend_on("a", "b").unwrap();
+        fork.depend_on("a", "c").unwrap();
+        assert!(!chain.is_isomorphic_to(&fork));
+        assert!(AcyclicDependencyGraph::<u8>::new()
+            .is_isomorphic_to(&AcyclicDependencyGraph::<char>::new()));
+    }
+
+    #[test]
+    fn transitive_closure_matches_reachability() {
+        let graph = cake_graph();
+        let closure = graph.transitive_closure();
+        assert_eq!(closure.nodes, graph.nodes);
+        for from in &graph.nodes {
+            for to in &graph.nodes {
+                let direct = closure
+                    .forward_dependencies
+                    .get(from)
+                    .is_some_and(|deps| deps.contains(to));
+                assert_eq!(direct, graph.depends_on(from, to));
+                let reverse = closure
+                    .backward_dependencies
+                    .get(to)
+                    .is_some_and(|deps| deps.contains(from));
+                assert_eq!(reverse, direct);
+            }
+        }
+    }
+
+    #[test]
+    fn find_self_references_reports_all() {
+        let edges = [("a", "b"), ("b", "b"), ("b", "c"), ("d", "d"), ("c", "a")];
+        assert_eq!(
+            AcyclicDependencyGraph::find_self_references(&edges),
+            vec![("b", "b"), ("d", "d")]
+        );
+        assert!(AcyclicDependencyGraph::find_self_references(&edges[..1]).is_empty());
+    }
+
+    #[test]
+    fn collect_forward_dependencies_into_reuses_buffer() {
+        let graph = cake_graph();
+        let mut buffer = HashSet::new();
+        for node in ["cake", "eggs", "grain", "soil", "chickens", "cake"] {
+            graph.collect_forward_dependencies_into(node, &mut buffer);
+            assert_eq!(buffer, graph.get_forward_dependencies(&node));
+        }
+    }
+
+    #[test]
+    fn weak_connectivity() {
+        assert!(AcyclicDependencyGraph::<&str>::new().is_weakly_connected());
+        assert!(cake_graph().is_weakly_connected());
+
+        let mut graph = AcyclicDependencyGraph::new();
+        graph.depend_on("a", "b").unwrap();
+        graph.depend_on("b", "c").unwrap();
+        graph.depend_on("x", "y").unwrap();
+        graph.depend_on("y", "z").unwrap();
+        assert!(!graph.is_weakly_connected());
+
+        graph.depend_on("z", "b").unwrap();
+        assert!(graph.is_weakly_connected());
+    }
+
+    #[test]
+    fn layers_with_long_edges_finds_layer_skipping_edges() {
+        let graph = cake_graph();
+        let (layers, mut long_edges) = graph.layers_with_long_edges();
+        assert_eq!(layers, graph.get_forward_dependency_topological_layers());
+        long_edges.sort();
+        assert_eq!(long_edges, vec![("cake", "flour"), ("chickens", "water")]);
+    }
+
+    #[test]
+    fn random_mutations_keep_dependency_maps_consistent() {
+        // xorshift keeps the sequence reproducible without extra dependencies
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = |bound: u64| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state % bound
+        };
+
+        for _ in 0..20 {
+            let mut graph = AcyclicDependencyGraph::new();
+            for _ in 0..200 {
+                match next(4) {
+                    0 => {
+                        graph.remove_edge(&next(16), &next(16));
+                    }
+                    1 => {
+                        let node = next(16);
+                        graph.retain_edges(|from, to| *from != node && *to != node);
+                    }
+                    _ => {
+                        let _ = graph.depend_on(next(16), next(16));
+                    }
+                }
+                graph.assert_consistent();
+            }
+        }
+    }
+
+    #[test]
+    fn degree_stats_summarize_cake_graph() {
+        let stats = cake_graph().degree_stats();
+        assert_eq!((stats.min_out_degree, stats.max_out_degree), (0, 2));
+        assert_eq!((stats.min_in_degree, stats.max_in_degree), (0, 2));
+        assert!((stats.mean_out_degree - 8.0 / 7.0).abs() < 1e-9);
+        assert!((stats.mean_in_degree - 8.0 / 7.0).abs() < 1e-9);
+        assert!(["cake", "chickens", "grain"].contains(&stats.max_out_degree_node.unwrap()));
+        assert!(["grain", "water"].contains(&stats.max_in_degree_node.unwrap()));
+
+        let empty = AcyclicDependencyGraph::<&str>::new().degree_stats();
+        assert_eq!(empty.max_in_degree_node, None);
+        assert_eq!(empty.mean_out_degree, 0.0);
+    }
+
+    #[test]
+    fn direct_dependents_of_grain() {
+        let graph = cake_graph();
+        let dependents: HashSet<&&str> = graph.direct_dependents(&"grain").collect();
+        assert_eq!(dependents, HashSet::from([&"flour", &"chickens"]));
+        assert_eq!(graph.direct_dependents(&"cake").count(), 0);
+        assert_eq!(graph.direct_dependents(&"missing").count(), 0);
+    }
+
+    #[test]
+    fn articulation_points_find_bottleneck() {
+        let mut graph = AcyclicDependencyGraph::new();
+        graph.depend_on("a", "b").unwrap();
+        graph.depend_on("a", "c").unwrap();
+        graph.depend_on("b", "x").unwrap();
+        graph.depend_on("c", "x").unwrap();
+        graph.depend_on("x", "d").unwrap();
+        graph.depend_on("x", "e").unwrap();
+        graph.depend_on("d", "f").unwrap();
+        graph.depend_on("e", "f").unwrap();
+        assert_eq!(graph.articulation_points(), HashSet::from(["x"]));
+
+        assert_eq!(cake_graph().articulation_points(), HashSet::from(["grain"]));
+    }
+
+    #[test]
+    fn is_on_path_in_cake_graph() {
+        let graph = cake_graph();
+        assert!(graph.is_on_path("grain", "cake", "soil"));
+        assert!(graph.is_on_path("chickens", "eggs", "water"));
+        assert!(graph.is_on_path("cake", "cake", "soil"));
+        assert!(!graph.is_on_path("flour", "eggs", "soil"));
+        assert!(!graph.is_on_path("grain", "soil", "cake"));
+        assert!(!graph.is_on_path("water", "cake", "soil"));
+    }
+
+    #[test]
+    fn to_csv_escapes_values() {
+        let mut graph = AcyclicDependencyGraph::new();
+        graph.depend_on("cake", "eggs, large").unwrap();
+        graph.depend_on("cake", "\"flour\"").unwrap();
+        graph.depend_on("eggs, large", "chickens").unwrap();
+        let csv = graph.to_csv();
+        let mut lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.remove(0), "from,to");
+        lines.sort();
+        assert_eq!(
+            lines,
+            vec![
+                "\"eggs, large\",chickens",
+                "cake,\"\"\"flour\"\"\"",
+                "cake,\"eggs, large\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn every_node_reaches_a_leaf() {
+        assert!(cake_graph().nodes_not_reaching_any_leaf().is_empty());
+        assert!(AcyclicDependencyGraph::<&str>::new()
+            .nodes_not_reaching_any_leaf()
+            .is_empty());
+    }
+
+    #[test]
+    fn min_path_cover_of_known_graphs() {
+        assert_eq!(cake_graph().min_path_cover(), 2);
+        assert_eq!(AcyclicDependencyGraph::<&str>::new().min_path_cover(), 0);
+
+        let mut fan = AcyclicDependencyGraph::new();
+        fan.depend_on("a", "b").unwrap();
+        fan.depend_on("a", "c").unwrap();
+        fan.depend_on("a", "d").unwrap();
+        fan.depend_on("d", "e").unwrap();
+        assert_eq!(fan.min_path_cover(), 3);
+    }
+
+    #[test]
+    fn reverse_topological_iter_yields_dependents_first() {
+        let graph = cake_graph();
+        let order: Vec<&str> = graph.reverse_topological_iter().collect();
+        assert_eq!(order.len(), graph.nodes.len());
+        let position = |node: &&str| order.iter().position(|n| n == node).unwrap();
+        for (from, deps) in &graph.forward_dependencies {
+            for to in deps {
+                assert!(position(from) < position(to));
+            }
+        }
+        assert_eq!(order[0], "cake");
+    }
+
+    #[test]
+    fn pruned_graph_is_subgraph() {
+        let graph = cake_graph();
+        let mut pruned = AcyclicDependencyGraph::new();
+        pruned.depend_on("cake", "flour").unwrap();
+        pruned.depend_on("flour", "grain").unwrap();
+        pruned.depend_on("grain", "soil").unwrap();
+        assert!(pruned.is_subgraph_of(&graph));
+        assert!(graph.is_subgraph_of(&graph));
+        assert!(!graph.is_subgraph_of(&pruned));
+
+        // "cake" reaches "soil" in the original, but not directly
+        pruned.depend_on("cake", "soil").unwrap();
+        assert!(!pruned.is_subgraph_of(&graph));
+    }
+
+    #[test]
+    fn get_forward_dependencies_cow_borrows_shallow_sets() {
+        let graph = cake_graph();
+        for node in &graph.nodes {
+            let cow = graph.get_forward_dependencies_cow(node);
+            assert_eq!(*cow, graph.get_forward_dependencies(node));
+            let expect_borrowed = matches!(*node, "grain");
+            assert_eq!(matches!(cow, Cow::Borrowed(_)), expect_borrowed);
+        }
+    }
+
+    #[test]
+    fn longest_edge_reports_span() {
+        let mut graph = AcyclicDependencyGraph::new();
+        assert_eq!(graph.longest_edge(), None);
+        graph.depend_on("a", "b").unwrap();
+        assert_eq!(graph.longest_edge(), Some(("a", "b", 0)));
+        graph.depend_on("b", "c").unwrap();
+        graph.depend_on("c", "d").unwrap();
+        graph.depend_on("a", "d").unwrap();
+        graph.depend_on("b", "d").unwrap();
+        assert_eq!(graph.longest_edge(), Some(("a", "d", 2)));
+    }
+
+    #[test]
+    fn dependency_entry_adds_only_when_vacant() {
+        let mut graph = cake_graph();
+        graph.dependency_entry("soil").or_depend_on("rock").unwrap();
+        graph.dependency_entry("eggs").or_depend_on("rock").unwrap();
+        assert!(graph.contains_edge(&"soil", &"rock"));
+        assert!(!graph.contains_edge(&"eggs", &"rock"));
+
+        graph
+            .dependency_entry("flour")
+            .and_modify(|entry| entry.depend_on("mill"))
+            .unwrap()
+            .or_depend_on("unused")
+            .unwrap();
+        graph
+            .dependency_entry("water")
+            .and_modify(|entry| entry.depend_on("unused"))
+            .unwrap();
+        assert!(graph.contains_edge(&"flour", &"mill"));
+        assert!(!graph.nodes.contains("unused"));
+
+        let entry = graph.dependency_entry("rock").or_depend_on("cake");
+        assert!(matches!(entry, Err(DependencyError::CircularDependency)));
+    }
+
+    #[test]
+    fn forward_walk_with_depth_reports_minimum_hops() {
+        let graph = cake_graph();
+        let walk = graph.forward_walk_with_depth("cake");
+        assert_eq!(walk[0], ("cake", 0));
+        let depths: HashMap<&str, usize> = walk.into_iter().collect();
+        assert_eq!(
+            depths,
+            HashMap::from([
+                ("cake", 0),
+                ("eggs", 1),
+                ("flour", 1),
+                ("chickens", 2),
+                ("grain", 2),
+                ("soil", 3),
+                ("water", 3),
+            ])
+        );
+        assert_eq!(graph.forward_walk_with_depth("soil"), vec![("soil", 0)]);
+        assert!(graph.forward_walk_with_depth("missing").is_empty());
+    }
+
+    #[test]
+    fn valid_frontiers_are_downward_closed() {
+        let graph = cake_graph();
+        assert!(graph.is_valid_frontier(&HashSet::new()));
+        assert!(graph.is_valid_frontier(&HashSet::from(["soil", "water"])));
+        assert!(graph.is_valid_frontier(&HashSet::from(["soil", "water", "grain", "flour"])));
+        assert!(!graph.is_valid_frontier(&HashSet::from(["soil", "grain"])));
+        assert!(!graph.is_valid_frontier(&HashSet::from(["soil", "water", "chickens"])));
+    }
+
+    #[test]
+    fn nodes_by_descendant_count_ranks_cake_first() {
+        let ranking = cake_graph().nodes_by_descendant_count();
+        assert_eq!(ranking.len(), 7);
+        assert_eq!(ranking[0], ("cake", 6));
+        assert_eq!(ranking[1], ("eggs", 4));
+        assert!(ranking.windows(2).all(|w| w[0].1 >= w[1].1));
+        assert_eq!(ranking[5].1, 0);
+        assert_eq!(ranking[6].1, 0);
+    }
+
+    #[test]
+    fn structural_fingerprint_tracks_structure() {
+        let graph = cake_graph();
+        assert_eq!(
+            graph.structural_fingerprint(),
+            cake_graph().structural_fingerprint()
+        );
+        assert_eq!(
+            graph.structural_fingerprint(),
+            graph.clone().structural_fingerprint()
+        );
+
+        let mut changed = cake_graph();
+        changed.depend_on("cake", "water").unwrap();
+        assert_ne!(
+            graph.structural_fingerprint(),
+            changed.structural_fingerprint()
+        );
+        assert_ne!(
+            graph.structural_fingerprint(),
+            AcyclicDependencyGraph::<&str>::new().structural_fingerprint()
+        );
+    }
+
+    #[test]
+    fn schedule_batches_are_ordered() {
+        let graph = cake_graph();
+        let batches = graph.schedule_batches();
+        assert_eq!(
+            batches,
+            vec![
+                vec!["soil", "water"],
+                vec!["grain"],
+                vec!["chickens", "flour"],
+                vec!["eggs"],
+                vec!["cake"],
+            ]
+        );
+        for (i, batch) in batches.iter().enumerate() {
+            for node in batch {
+                for dependency in graph.get_forward_dependencies(node) {
+                    assert!(batches[..i]
+                        .iter()
+                        .any(|earlier| earlier.contains(&dependency)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn orphans_if_removed_matches_actual_removal() {
+        let graph = cake_graph();
+        for node in ["grain", "chickens", "eggs", "cake", "soil"] {
+            let predicted = graph.orphans_if_removed(node);
+
+            let mut removed = graph.clone();
+            removed.retain_edges(|from, to| *from != node && *to != node);
+            removed.nodes.remove(node);
+            let mut reachable: HashSet<&str> = HashSet::new();
+            for root in graph.get_roots().into_iter().filter(|root| *root != node) {
+                reachable.insert(root);
+                reachable.extend(removed.get_forward_dependencies(&root));
+            }
+            let actual: HashSet<&str> = removed.nodes.difference(&reachable).copied().collect();
+            assert_eq!(predicted, actual, "removing {}", node);
+        }
+        assert_eq!(graph.orphans_if_removed("grain"), HashSet::from(["soil"]));
+        assert!(graph.orphans_if_removed("chickens").is_empty());
+    }
+
+    #[test]
+    fn dependencies_by_root_shares_subtrees() {
+        let mut graph = AcyclicDependencyGraph::new();
+        graph.depend_on("app", "ui").unwrap();
+        graph.depend_on("app", "core").unwrap();
+        graph.depend_on("cli", "core").unwrap();
+        graph.depend_on("core", "alloc").unwrap();
+        let by_root = graph.dependencies_by_root();
+        assert_eq!(by_root.len(), 2);
+        assert_eq!(by_root["app"], HashSet::from(["ui", "core", "alloc"]));
+        assert_eq!(by_root["cli"], HashSet::from(["core", "alloc"]));
+        let shared: HashSet<&str> = by_root["app"]
+            .intersection(&by_root["cli"])
+            .copied()
+            .collect();
+        assert_eq!(shared, HashSet::from(["core", "alloc"]));
+    }
+
+    #[test]
+    fn structurally_equal_graphs_dedupe() {
+        let mut reordered = AcyclicDependencyGraph::new();
+        reordered.depend_on("chickens", "water").unwrap();
+        reordered.depend_on("grain", "water").unwrap();
+        reordered.depend_on("grain", "soil").unwrap();
+        reordered.depend_on("chickens", "grain").unwrap();
+        reordered.depend_on("flour", "grain").unwrap();
+        reordered.depend_on("eggs", "chickens").unwrap();
+        reordered.depend_on("cake", "flour").unwrap();
+        reordered.depend_on("cake", "eggs").unwrap();
+        assert!(reordered == cake_graph());
+
+        let mut changed = cake_graph();
+        changed.depend_on("cake", "water").unwrap();
+        assert!(changed != cake_graph());
+
+        let graphs = HashSet::from([cake_graph(), reordered, changed]);
+        assert_eq!(graphs.len(), 2);
+    }
+
+    #[test]
+    fn minimal_leaf_requirements_of_cake() {
+        let graph = cake_graph();
+        assert_eq!(
+            graph.minimal_leaf_requirements("cake"),
+            HashSet::from(["soil", "water"])
+        );
+        assert_eq!(
+            graph.minimal_leaf_requirements("soil"),
+            HashSet::from(["soil"])
+        );
+        assert!(graph.minimal_leaf_requirements("missing").is_empty());
+    }
+
+    #[test]
+    fn edge_betweenness_highlights_bottleneck() {
+        let mut graph = AcyclicDependencyGraph::new();
+        graph.depend_on("a", "x").unwrap();
+        graph.depend_on("b", "x").unwrap();
+        graph.depend_on("x", "y").unwrap();
+        graph.depend_on("y", "c").unwrap();
+        graph.depend_on("y", "d").unwrap();
+        let scores = graph.edge_betweenness();
+        assert_eq!(scores.len(), 5);
+        assert_eq!(scores[&("x", "y")], 9.0);
+        assert_eq!(scores[&("a", "x")], 4.0);
+        assert_eq!(scores[&("y", "c")], 4.0);
+
+        // the two paths from "a" to "d" split the pair between them
+        let mut diamond = AcyclicDependencyGraph::new();
+        diamond.depend_on("a", "b").unwrap();
+        diamond.depend_on("a", "c").unwrap();
+        diamond.depend_on("b", "d").unwrap();
+        diamond.depend_on("c", "d").unwrap();
+        let scores = diamond.edge_betweenness();
+        assert_eq!(scores[&("a", "b")], 1.5);
+        assert_eq!(scores[&("c", "d")], 1.5);
+    }
+
+    #[test]
+    fn unique_topological_order() {
+        let mut chain = AcyclicDependencyGraph::new();
+        chain.depend_on("a", "b").unwrap();
+        chain.depend_on("b", "c").unwrap();
+        chain.depend_on("a", "c").unwrap();
+        assert!(chain.has_unique_topological_order());
+
+        let mut diamond = AcyclicDependencyGraph::new();
+        diamond.depend_on("a", "b").unwrap();
+        diamond.depend_on("a", "c").unwrap();
+        diamond.depend_on("b", "d").unwrap();
+        diamond.depend_on("c", "d").unwrap();
+        assert!(!diamond.has_unique_topological_order());
+        assert!(!cake_graph().has_unique_topological_order());
+    }
+
+    #[test]
+    fn retain_edges_keeps_matching_edges() {
+        let mut graph = cake_graph();
+        let leaves = graph.get_leaves();
+        graph.retain_edges(|_, to| leaves.contains(to));
+
+        let mut edges: Vec<(&str, &str)> = graph
+            .forward_dependencies
+            .iter()
+            .flat_map(|(from, deps)| deps.iter().map(move |to| (*from, *to)))
+            .collect();
+        edges.sort();
+        assert_eq!(
+            edges,
+            vec![("chickens", "water"), ("grain", "soil"), ("grain", "water")]
+        );
+        assert_eq!(graph.nodes, cake_graph().nodes);
+        assert_eq!(
+            graph.get_roots(),
+            HashSet::from(["cake", "eggs", "flour", "chickens", "grain"])
+        );
+        assert!(graph.get_leaves().contains("cake"));
+    }
+
+    #[test]
+    fn condense_groups_cake_ingredients() {
+        let graph = cake_graph();
+        let condensed = graph.condense(|node| match *node {
+            "cake" => "dessert",
+            "eggs" | "flour" => "ingredient",
+            "chickens" | "grain" => "farm",
+            _ => "nature",
+        });
+        let mut expected = AcyclicDependencyGraph::new();
+        expected.depend_on("dessert", "ingredient").unwrap();
+        expected.depend_on("ingredient", "farm").unwrap();
+        expected.depend_on("farm", "nature").unwrap();
+        assert!(condensed == expected);
+        assert_eq!(condensed.duplicate_edge_insert_count(), 0);
+
+        let single = graph.condense(|_| 0);
+        assert_eq!(single.nodes, HashSet::from([0]));
+        assert!(single.forward_dependencies.is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn condense_panics_on_cyclic_grouping() {
+        let mut graph = AcyclicDependencyGraph::new();
+        graph.depend_on("a", "b").unwrap();
+        graph.depend_on("b", "c").unwrap();
+        graph.condense(|node| *node == "b");
+    }
+
+    #[test]
+    fn depend_on_tracked_reports_gained_dependencies() {
+        let mut graph = cake_graph();
+        graph.depend_on("salt", "sea").unwrap();
+        let before = graph.get_forward_dependencies(&"flour");
+        let gained = graph.depend_on_tracked("flour", "salt").unwrap();
+        let after = graph.get_forward_dependencies(&"flour");
+        assert_eq!(gained, after.difference(&before).copied().collect());
+        assert_eq!(gained, HashSet::from(["salt", "sea"]));
+
+        assert!(graph.depend_on_tracked("cake", "water").unwrap().is_empty());
+        assert!(graph.depend_on_tracked("sea", "cake").is_err());
+    }
+
+    #[test]
+    fn edges_topologically_orders_incoming_first() {
+        let graph = cake_graph();
+        let edges = graph.edges_topologically();
+        assert_eq!(edges.len(), 8);
+        for (i, (from, _)) in edges.iter().enumerate() {
+            for (j, (_, to)) in edges.iter().enumerate() {
+                if to == from {
+                    assert!(j < i, "({:?}) must precede ({:?})", edges[j], edges[i]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn blast_radius_of_grain() {
+        let graph = cake_graph();
+        assert_eq!(
+            graph.blast_radius(&["grain"]),
+            HashSet::from(["grain", "flour", "chickens", "eggs", "cake"])
+        );
+        assert_eq!(
+            graph.blast_radius(&["eggs", "flour"]),
+            HashSet::from(["eggs", "flour", "cake"])
+        );
+        assert!(graph.blast_radius(&[]).is_empty());
+    }
+
+    #[test]
+    fn nodes_at_forward_depth_matches_layers() {
+        let graph = cake_graph();
+        assert_eq!(graph.nodes_at_forward_depth(0), graph.get_leaves());
+        for (depth, layer) in graph
+            .get_forward_dependency_topological_layers()
+            .into_iter()
+            .enumerate()
+        {
+            assert_eq!(graph.nodes_at_forward_depth(depth), layer);
+        }
+        assert!(graph.nodes_at_forward_depth(5).is_empty());
+        assert!(graph.nodes_at_forward_depth(100).is_empty());
+    }
+
+    #[test]
+    fn duplicate_edge_inserts_are_counted() {
+        let mut graph = AcyclicDependencyGraph::new();
+        graph.depend_on("a", "b").unwrap();
+        assert_eq!(graph.duplicate_edge_insert_count(), 0);
+        graph.depend_on("a", "b").unwrap();
+        assert_eq!(graph.duplicate_edge_insert_count(), 1);
+        graph.depend_on("b", "c").unwrap();
+        graph.depend_on("a", "c").unwrap();
+        assert_eq!(graph.duplicate_edge_insert_count(), 1);
+
+        graph.clear();
+        assert!(graph.is_empty());
+        assert_eq!(graph.duplicate_edge_insert_count(), 0);
+    }
+
+    #[test]
+    fn ancestors_by_depth_of_grain() {
+        let graph = cake_graph();
+        let ancestors = graph.ancestors_by_depth("grain");
+        assert!(ancestors.windows(2).all(|w| w[0].1 <= w[1].1));
+        let depths: HashMap<&str, usize> = ancestors.into_iter().collect();
+        assert_eq!(
+            depths,
+            HashMap::from([("flour", 1), ("chickens", 1), ("eggs", 2), ("cake", 2)])
+        );
+        assert!(graph.ancestors_by_depth("cake").is_empty());
+    }
+
+    #[test]
+    fn structural_twins_share_neighbors() {
+        let mut graph = cake_graph();
+        assert_eq!(graph.structural_twins().len(), 0);
+
+        graph.depend_on("cake", "sugar").unwrap();
+        graph.depend_on("cake", "butter").unwrap();
+        graph.depend_on("sugar", "water").unwrap();
+        graph.depend_on("butter", "water").unwrap();
+        let twins = graph.structural_twins();
+        assert_eq!(twins.len(), 1);
+        let (a, b) = twins[0];
+        assert_eq!(HashSet::from([a, b]), HashSet::from(["sugar", "butter"]));
+    }
+
+    #[test]
+    fn irreversible_edges_close_loops() {
+        let mut graph = AcyclicDependencyGraph::new();
+        graph.depend_on("a", "b").unwrap();
+        graph.depend_on("b", "c").unwrap();
+        graph.depend_on("a", "c").unwrap();
+        graph.depend_on("c", "d").unwrap();
+        assert_eq!(graph.irreversible_edges(), vec![("a", "c")]);
+
+        let mut irreversible = cake_graph().irreversible_edges();
+        irreversible.sort();
+        assert_eq!(irreversible, vec![("chickens", "water")]);
+    }
+
+    #[test]
+    fn random_topological_order_is_reproducible() {
+        let graph = cake_graph();
+        let order = graph.random_topological_order(42);
+        assert_eq!(order, cake_graph().random_topological_order(42));
+        assert_eq!(
+            order,
+            vec!["water", "soil", "grain", "chickens", "flour", "eggs", "cake"]
+        );
+
+        let mut distinct = HashSet::new();
+        for seed in 0..16 {
+            let order = graph.random_topological_order(seed);
+            assert_eq!(order.len(), graph.nodes.len());
+            for (from, deps) in &graph.forward_dependencies {
+                let position = |node| order.iter().position(|n| n == node).unwrap();
+                for to in deps {
+                    assert!(position(to) < position(from));
+                }
+            }
+            distinct.insert(order);
+        }
+        assert!(distinct.len() > 1);
+    }
+
+    #[test]
+    fn nodes_by_dependent_count_ranks_leaves_first() {
+        let graph = cake_graph();
+        assert_eq!(graph.transitive_dependent_count("grain"), 4);
+        assert_eq!(graph.transitive_dependent_count("cake"), 0);
+
+        let ranking = graph.nodes_by_dependent_count();
+        assert_eq!(ranking.len(), 7);
+        let top: HashSet<&str> = ranking[..2].iter().map(|(node, _)| *node).collect();
+        assert_eq!(top, HashSet::from(["soil", "water"]));
+        assert_eq!(ranking[0].1, 5);
+        assert_eq!(ranking[2], ("grain", 4));
+        assert_eq!(ranking[6], ("cake", 0));
+    }
+
+    #[test]
+    fn validates_topological_orders() {
+        let graph = cake_graph();
+        let order = [
+            "soil", "water", "grain", "flour", "chickens", "eggs", "cake",
+        ];
+        assert!(graph.is_valid_topological_order(&order));
+        assert!(graph.is_valid_topological_order(&graph.random_topological_order(7)));
+
+        let swapped = [
+            "soil", "water", "grain", "flour", "eggs", "chickens", "cake",
+        ];
+        assert!(!graph.is_valid_topological_order(&swapped));
+        let repeated = [
+            "soil", "water", "grain", "flour", "chickens", "eggs", "eggs",
+        ];
+        assert!(!graph.is_valid_topological_order(&repeated));
+        assert!(!graph.is_valid_topological_order(&order[..6]));
+    }
+
+    #[test]
+    fn shared_between_eggs_and_flour() {
+        let graph = cake_graph();
+        assert_eq!(
+            graph.shared_between("eggs", "flour"),
+            HashSet::from(["grain", "soil", "water"])
+        );
+        assert_eq!(
+            graph.shared_between("chickens", "grain"),
+            HashSet::from(["grain", "soil", "water"])
+        );
+        assert!(graph.shared_between("soil", "water").is_empty());
+    }
+
+    #[test]
+    fn to_mermaid_emits_flowchart() {
+        let mut graph = AcyclicDependencyGraph::new();
+        graph.depend_on("cake", "brown sugar").unwrap();
+        graph.depend_on("a-b", "a_b").unwrap();
+        graph.depend_on("cake", "\"#1\" cane").unwrap();
+        assert_eq!(
+            graph.to_mermaid(),
+            concat!(
+                "flowchart TD\n",
+                "    n0[\"#quot;#35;1#quot; cane\"]\n",
+                "    n1[\"a-b\"]\n",
+                "    n2[\"a_b\"]\n",
+                "    n3[\"brown sugar\"]\n",
+                "    n4[\"cake\"]\n",
+                "    n1 --> n2\n",
+                "    n4 --> n0\n",
+                "    n4 --> n3\n",
+            )
+        );
+        assert_eq!(
+            AcyclicDependencyGraph::<&str>::new().to_mermaid(),
+            "flowchart TD\n"
+        );
+    }
+
+    #[test]
+    fn average_depth_of_cake_graph() {
+        assert!((cake_graph().average_depth() - 12.0 / 7.0).abs() < 1e-9);
+        assert_eq!(AcyclicDependencyGraph::<&str>::new().average_depth(), 0.0);
+    }
+
+    #[test]
+    fn longest_path_of_cake_graph() {
+        let graph = cake_graph();
+        let path = graph.longest_path();
+        assert_eq!(path.len(), 5);
+        assert_eq!(path[..4], ["cake", "eggs", "chickens", "grain"]);
+        assert!(["soil", "water"].contains(&path[4]));
+        assert!(AcyclicDependencyGraph::<&str>::new()
+            .longest_path()
+            .is_empty());
+    }
+
+    #[test]
+    fn reachability_index_matches_depends_on() {
+        let graph = cake_graph();
+        let index = graph.build_reachability_index();
+        for from in &graph.nodes {
+            for to in &graph.nodes {
+                assert_eq!(index.reaches(from, to), graph.depends_on(from, to));
+            }
+        }
+        assert!(!index.reaches(&"cake", &"missing"));
+
+        // rows span several words
+        let edges: Vec<(usize, usize)> = (0..150).map(|i| (i, i + 1)).collect();
+        let mut chain = AcyclicDependencyGraph::new();
+        chain.load_edges_with_progress(&edges, |_, _| {}).unwrap();
+        let index = chain.build_reachability_index();
+        assert!(index.reaches(&0, &150));
+        assert!(index.reaches(&70, &130));
+        assert!(!index.reaches(&130, &70));
+        assert!(!index.reaches(&64, &64));
+    }
+
+    #[test]
+    fn incomparable_pairs_of_cake_graph() {
+        let pairs: HashSet<(&str, &str)> = cake_graph()
+            .incomparable_pairs()
+            .into_iter()
+            .map(|(a, b)| if a < b { (a, b) } else { (b, a) })
+            .collect();
+        assert_eq!(
+            pairs,
+            HashSet::from([("soil", "water"), ("eggs", "flour"), ("chickens", "flour"),])
+        );
+    }
+
+    #[test]
+    fn shortcut_edges_are_redundant() {
+        let mut graph = cake_graph();
+        graph.depend_on("cake", "grain").unwrap();
+        assert!(graph.is_edge_redundant(&"cake", &"grain"));
+        assert!(graph.is_edge_redundant(&"chickens", &"water"));
+        assert!(!graph.is_edge_redundant(&"grain", &"soil"));
+        assert!(!graph.is_edge_redundant(&"eggs", &"chickens"));
+        assert!(!graph.is_edge_redundant(&"cake", &"soil"));
+    }
+
+    #[test]
+    fn subtree_forward_layers_of_eggs() {
+        let graph = cake_graph();
+        assert_eq!(
+            graph.subtree_forward_layers("eggs"),
+            vec![
+                HashSet::from(["soil", "water"]),
+                HashSet::from(["grain"]),
+                HashSet::from(["chickens"]),
+                HashSet::from(["eggs"]),
+            ]
+        );
+        assert_eq!(
+            graph.subtree_forward_layers("soil"),
+            vec![HashSet::from(["soil"])]
+        );
+        assert!(graph.subtree_forward_layers("missing").is_empty());
+    }
+
+    #[test]
+    fn divergence_point_of_cake_paths() {
+        let graph = cake_graph();
+        assert_eq!(
+            graph.divergence_point("cake", "chickens", "grain"),
+            Some("cake")
+        );
+        assert_eq!(
+            graph.divergence_point("cake", "eggs", "chickens"),
+            Some("eggs")
+        );
+        assert_eq!(
+            graph.divergence_point("eggs", "soil", "water"),
+            Some("chickens")
+        );
+        assert_eq!(graph.divergence_point("cake", "cake", "soil"), Some("cake"));
+        assert_eq!(graph.divergence_point("flour", "soil", "chickens"), None);
+        assert_eq!(graph.divergence_point("soil", "soil", "soil"), Some("soil"));
+    }
+
+    #[test]
+    fn with_capacity_preallocates() {
+        let edges: Vec<(usize, usize)> = (0..999).map(|i| (i, i + 1)).collect();
+        let mut graph = AcyclicDependencyGraph::with_capacity(1000);
+        assert!(graph.is_empty());
+        assert!(graph.nodes.capacity() >= 1000);
+        assert!(graph.forward_dependencies.capacity() >= 1000);
+        assert!(graph.backward_dependencies.capacity() >= 1000);
+
+        let mut unsized_graph = AcyclicDependencyGraph::new();
+        graph.load_edges_with_progress(&edges, |_, _| {}).unwrap();
+        unsized_graph
+            .load_edges_with_progress(&edges, |_, _| {})
+            .unwrap();
+        assert_eq!(graph.nodes.len(), 1000);
+        assert!(graph == unsized_graph);
+    }
+
+    #[test]
+    fn shrink_to_fit_keeps_contents() {
+        let mut graph = AcyclicDependencyGraph::with_capacity(1000);
+        for (from, to) in [("cake", "eggs"), ("cake", "flour"), ("eggs", "chickens")] {
+            graph.depend_on(from, to).unwrap();
+        }
+        let before = graph.clone();
+        graph.shrink_to_fit();
+        assert!(graph == before);
+        assert!(graph.nodes.capacity() < 1000);
+        assert!(graph.depends_on(&"cake", &"chickens"));
+    }
+
+    #[test]
+    fn merge_mapped_combines_namespaces() {
+        let mut frontend = AcyclicDependencyGraph::new();
+        frontend.depend_on("web/app", "web/ui").unwrap();
+        frontend.depend_on("web/ui", "lib/core").unwrap();
+        let mut backend = AcyclicDependencyGraph::new();
+        backend.depend_on("api/server", "lib/core").unwrap();
+        backend.depend_on("lib/core", "lib/alloc").unwrap();
+        backend.depend_on("api/ui", "lib/core").unwrap();
+
+        let collapsed = AcyclicDependencyGraph::merge_mapped(&[&frontend, &backend], |_| 0);
+        assert!(matches!(collapsed, Err(DependencyError::SelfReference)));
+
+        let strip = |node: &&'static str| node.split_once('/').unwrap().1;
+        let merged = AcyclicDependencyGraph::merge_mapped(&[&frontend, &backend], strip).unwrap();
+        let mut expected = AcyclicDependencyGraph::new();
+        expected.depend_on("app", "ui").unwrap();
+        expected.depend_on("ui", "core").unwrap();
+        expected.depend_on("server", "core").unwrap();
+        expected.depend_on("core", "alloc").unwrap();
+        assert!(merged == expected);
+        assert_eq!(merged.duplicate_edge_insert_count(), 0);
+
+        let mut cyclic = AcyclicDependencyGraph::new();
+        cyclic.depend_on("x/alloc", "x/app").unwrap();
+        let result = AcyclicDependencyGraph::merge_mapped(&[&frontend, &backend, &cyclic], strip);
+        assert!(matches!(result, Err(DependencyError::CircularDependency)));
+    }
+
+    #[test]
+    fn leaf_and_root_checks() {
+        let graph = cake_graph();
+        let leaves = graph.get_leaves();
+        let roots = graph.get_roots();
+        for node in &graph.nodes {
+            assert_eq!(graph.is_leaf(node), leaves.contains(node));
+            assert_eq!(graph.is_root(node), roots.contains(node));
+        }
+        assert!(graph.is_leaf(&"soil"));
+        assert!(!graph.is_leaf(&"grain"));
+        assert!(graph.is_root(&"cake"));
+        assert!(!graph.is_root(&"eggs"));
+        assert!(!graph.is_leaf(&"missing"));
+        assert!(!graph.is_root(&"missing"));
+    }
+
+    #[test]
+    fn layer_widths_of_cake_graph() {
+        let graph = cake_graph();
+        assert_eq!(graph.layer_widths(), vec![2, 1, 2, 1, 1]);
+        assert_eq!(
+            graph.layer_widths().iter().sum::<usize>(),
+            graph.nodes.len()
+        );
+        assert!(AcyclicDependencyGraph::<&str>::new()
+            .layer_widths()
+            .is_empty());
+    }
+
+    #[test]
+    fn floors_push_back_dependents() {
+        let graph = cake_graph();
+        assert_eq!(graph.earliest_layer_with_floor("cake", HashMap::new()), 4);
+        assert_eq!(graph.earliest_layer_with_floor("flour", HashMap::new()), 2);
+
+        let floor = HashMap::from([("grain", 3)]);
+        assert_eq!(graph.earliest_layer_with_floor("grain", floor.clone()), 3);
+        assert_eq!(graph.earliest_layer_with_floor("flour", floor.clone()), 4);
+        assert_eq!(
+            graph.earliest_layer_with_floor("chickens", floor.clone()),
+            4
         );
-        fwd_bckwd_check(
-            "soil",
-            vec![],
-            vec!["grain", "eggs", "flour", "cake", "chickens"],
+        assert_eq!(graph.earliest_layer_with_floor("cake", floor.clone()), 6);
+        assert_eq!(graph.earliest_layer_with_floor("soil", floor), 0);
+
+        // a floor below the natural layer has no effect
+        assert_eq!(
+            graph.earliest_layer_with_floor("eggs", HashMap::from([("eggs", 1)])),
+            3
         );
-        fwd_bckwd_check(
-            "water",
-            vec![],
-            vec!["grain", "eggs", "flour", "cake", "chickens"],
+    }
+
+    #[test]
+    fn high_degree_nodes_flag_hubs() {
+        let mut graph = AcyclicDependencyGraph::new();
+        for consumer in ["a", "b", "c"] {
+            graph.depend_on(consumer, "hub").unwrap();
+        }
+        for provider in ["x", "y"] {
+            graph.depend_on("hub", provider).unwrap();
+        }
+        assert_eq!(graph.high_degree_nodes(4), HashSet::from(["hub"]));
+        assert!(graph.high_degree_nodes(5).is_empty());
+        assert_eq!(graph.high_degree_nodes(0).len(), 6);
+        assert_eq!(cake_graph().high_degree_nodes(3), HashSet::from(["grain"]));
+    }
+
+    #[test]
+    fn transitive_reduction_reports_removed_shortcuts() {
+        let mut graph = cake_graph();
+        graph.depend_on("cake", "grain").unwrap();
+        let (reduced, mut removed) = graph.transitive_reduction_with_removed();
+        removed.sort();
+        assert_eq!(removed, vec![("cake", "grain"), ("chickens", "water")]);
+
+        let mut expected = cake_graph();
+        expected.retain_edges(|from, to| (*from, *to) != ("chickens", "water"));
+        assert!(reduced == expected);
+        for from in &graph.nodes {
+            for to in &graph.nodes {
+                assert_eq!(reduced.depends_on(from, to), graph.depends_on(from, to));
+            }
+        }
+    }
+
+    #[test]
+    fn subtrees_disjoint_checks() {
+        let mut graph = cake_graph();
+        assert!(!graph.subtrees_disjoint("eggs", "flour"));
+        assert!(graph.subtrees_disjoint("soil", "water"));
+
+        graph.depend_on("pie", "apples").unwrap();
+        graph.depend_on("apples", "trees").unwrap();
+        assert!(graph.subtrees_disjoint("pie", "cake"));
+        assert!(graph.subtrees_disjoint("apples", "eggs"));
+    }
+
+    #[test]
+    fn deep_chain_traversals_are_iterative() {
+        const DEPTH: usize = 100_000;
+        let mut graph = AcyclicDependencyGraph::with_capacity(DEPTH);
+        for i in 0..DEPTH - 1 {
+            graph.depend_on(i, i + 1).unwrap();
+        }
+
+        assert_eq!(graph.get_forward_dependencies(&0).len(), DEPTH - 1);
+        assert_eq!(
+            graph.get_backward_dependencies(&(DEPTH - 1)).len(),
+            DEPTH - 1
+        );
+        assert_eq!(
+            graph.get_forward_dependency_topological_layers().len(),
+            DEPTH
+        );
+        assert_eq!(
+            graph.get_backward_dependency_topological_layers().len(),
+            DEPTH
+        );
+        assert_eq!(graph.nodes_at_forward_depth(DEPTH - 1), HashSet::from([0]));
+
+        let order: Vec<usize> = graph.reverse_topological_iter().collect();
+        assert_eq!(order.len(), DEPTH);
+        assert_eq!(order[0], 0);
+        assert_eq!(
+            graph.random_topological_order(0),
+            (0..DEPTH).rev().collect::<Vec<_>>()
+        );
+        assert!(graph.has_unique_topological_order());
+        assert_eq!(graph.schedule_batches().len(), DEPTH);
+        assert_eq!(graph.edges_topologically().len(), DEPTH - 1);
+
+        assert_eq!(graph.longest_path().len(), DEPTH);
+        assert_eq!(graph.articulation_points().len(), DEPTH - 2);
+        assert_eq!(
+            graph.forward_walk_with_depth(0).last(),
+            Some(&(DEPTH - 1, DEPTH - 1))
+        );
+        assert_eq!(graph.min_vertex_cut(0, DEPTH - 1).len(), 1);
+        assert!(graph.is_weakly_connected());
+    }
+
+    #[test]
+    fn slack_is_zero_on_longest_path() {
+        let graph = cake_graph();
+        let slack = graph.slack();
+        assert_eq!(slack.len(), 7);
+        for node in graph.longest_path() {
+            assert_eq!(slack[&node], 0);
+        }
+        assert_eq!(slack["flour"], 1);
+        assert_eq!(slack["soil"], 0);
+        assert_eq!(slack["water"], 0);
+    }
+
+    #[test]
+    fn add_edge_and_update_layers_matches_fresh_layering() {
+        let mut graph = cake_graph();
+        let mut layers = graph.get_forward_dependency_topological_layers();
+        let edges = [
+            ("flour", "chickens"),
+            ("bread", "cake"),
+            ("salt", "flour"),
+            ("water", "rain"),
+            ("flour", "chickens"),
+        ];
+        for (from, to) in edges {
+            graph
+                .add_edge_and_update_layers(from, to, &mut layers)
+                .unwrap();
+            assert_eq!(layers, graph.get_forward_dependency_topological_layers());
+        }
+
+        let before = layers.clone();
+        assert!(graph
+            .add_edge_and_update_layers("rain", "bread", &mut layers)
+            .is_err());
+        assert_eq!(layers, before);
+    }
+
+    fn assert_json_matches(graph: &AcyclicDependencyGraph<&str>) {
+        let parsed: HashMap<String, Vec<String>> = serde_json::from_str(&graph.to_json()).unwrap();
+        let nodes: HashSet<&str> = parsed.keys().map(|node| node.as_str()).collect();
+        assert_eq!(nodes, graph.nodes);
+        for (node, deps) in &parsed {
+            let deps: HashSet<&str> = deps.iter().map(|dep| dep.as_str()).collect();
+            let expected = graph
+                .forward_dependencies
+                .get(node.as_str())
+                .cloned()
+                .unwrap_or_default();
+            assert_eq!(deps, expected);
+        }
+    }
+
+    #[test]
+    fn to_json_parses_to_the_graph() {
+        assert_json_matches(&cake_graph());
+        assert_json_matches(&AcyclicDependencyGraph::new());
+
+        let mut graph = AcyclicDependencyGraph::new();
+        graph.depend_on("cake", "\"eggs\"").unwrap();
+        graph.depend_on("cake", "flour\\grain").unwrap();
+        graph.depend_on("\"eggs\"", "hens\n").unwrap();
+        graph.depend_on("hens\n", "tab\tbell\u{7}").unwrap();
+        graph.depend_on("plate", "cake").unwrap();
+        graph.depend_on("fork", "plate").unwrap();
+        graph.retain_edges(|from, _| *from != "plate" && *from != "fork");
+        assert_json_matches(&graph);
+        assert!(graph.to_json().contains(r#""plate":[]"#));
+    }
+
+    #[test]
+    fn unlock_counts_prefer_sole_dependencies() {
+        let mut graph = cake_graph();
+        assert_eq!(
+            graph.unlock_counts(),
+            HashMap::from([("soil", 0), ("water", 0)])
+        );
+
+        graph.depend_on("frosting", "sugar").unwrap();
+        graph.depend_on("syrup", "sugar").unwrap();
+        graph.depend_on("syrup", "water").unwrap();
+        graph.depend_on("sprinkles", "sugar").unwrap();
+        let counts = graph.unlock_counts();
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts["sugar"], 2);
+        assert_eq!(
+            counts.iter().max_by_key(|(_, count)| **count).unwrap().0,
+            &"sugar"
+        );
+    }
+
+    #[test]
+    fn case_insensitive_collisions_find_typos() {
+        let mut graph = cake_graph();
+        assert!(graph.case_insensitive_collisions().is_empty());
+
+        graph.depend_on("flour", "Grain").unwrap();
+        graph.depend_on("Grain", "SOIL").unwrap();
+        assert_eq!(
+            graph.case_insensitive_collisions(),
+            vec![("Grain", "grain"), ("SOIL", "soil")]
+        );
+    }
+
+    #[test]
+    fn width_of_known_graphs() {
+        assert_eq!(cake_graph().width(), 2);
+        assert_eq!(AcyclicDependencyGraph::<&str>::new().width(), 0);
+
+        let mut fan = AcyclicDependencyGraph::new();
+        for dep in ["eggs", "flour", "sugar"] {
+            fan.depend_on("cake", dep).unwrap();
+        }
+        assert_eq!(fan.width(), 3);
+    }
+
+    fn adjacent_layer_crossings(
+        graph: &AcyclicDependencyGraph<&str>,
+        layers: &[Vec<&str>],
+    ) -> usize {
+        let mut crossings = 0;
+        for pair in layers.windows(2) {
+            let position = |layer: &[&str], node: &str| layer.iter().position(|n| *n == node);
+            let mut edges = Vec::new();
+            for (i, from) in pair[1].iter().enumerate() {
+                for to in graph.forward_dependencies.get(from).into_iter().flatten() {
+                    if let Some(j) = position(&pair[0], to) {
+                        edges.push((i, j));
+                    }
+                }
+            }
+            for (a, (i, j)) in edges.iter().enumerate() {
+                for (k, l) in &edges[a + 1..] {
+                    if (i < k && j > l) || (i > k && j < l) {
+                        crossings += 1;
+                    }
+                }
+            }
+        }
+        return crossings;
+    }
+
+    #[test]
+    fn ordered_layers_reduce_crossings() {
+        let mut graph = AcyclicDependencyGraph::new();
+        for (from, to) in [
+            ("a", "x"),
+            ("b", "y"),
+            ("c", "z"),
+            ("x", "p"),
+            ("y", "q"),
+            ("z", "r"),
+        ] {
+            graph.depend_on(from, to).unwrap();
+        }
+        let ordered = graph.ordered_layers();
+        let unordered: Vec<Vec<&str>> = graph
+            .get_forward_dependency_topological_layers()
+            .into_iter()
+            .map(|layer| layer.into_iter().collect())
+            .collect();
+        assert_eq!(adjacent_layer_crossings(&graph, &ordered), 0);
+        assert!(
+            adjacent_layer_crossings(&graph, &ordered)
+                <= adjacent_layer_crossings(&graph, &unordered)
+        );
+
+        let graph = cake_graph();
+        let ordered = graph.ordered_layers();
+        let layers: Vec<HashSet<&str>> = ordered
+            .iter()
+            .map(|layer| layer.iter().copied().collect())
+            .collect();
+        assert_eq!(layers, graph.get_forward_dependency_topological_layers());
+    }
+
+    #[test]
+    fn can_add_all_detects_collective_cycles() {
+        let graph = cake_graph();
+        assert!(graph
+            .can_add_all(&[("flour", "chickens"), ("plate", "cake")])
+            .is_ok());
+        assert!(!graph.depends_on(&"flour", &"chickens"));
+
+        let batch = [("soil", "rock"), ("rock", "bedrock"), ("bedrock", "cake")];
+        for edge in &batch {
+            assert!(graph.can_add_all(&[*edge]).is_ok());
+        }
+        assert!(matches!(
+            graph.can_add_all(&batch),
+            Err(DependencyError::InvalidEdge { index: 2, ref error })
+                if matches!(**error, DependencyError::CircularDependency)
+        ));
+        assert!(matches!(
+            graph.can_add_all(&[("eggs", "eggs")]),
+            Err(DependencyError::InvalidEdge { index: 0, ref error })
+                if matches!(**error, DependencyError::SelfReference)
+        ));
+        assert!(graph == cake_graph());
+    }
+
+    #[test]
+    fn dominators_of_cake_graph() {
+        let graph = cake_graph();
+        assert_eq!(
+            graph.dominators("cake"),
+            HashMap::from([
+                ("eggs", "cake"),
+                ("flour", "cake"),
+                ("chickens", "eggs"),
+                ("grain", "cake"),
+                ("soil", "grain"),
+                ("water", "cake"),
+            ])
+        );
+        assert_eq!(
+            graph.dominators("eggs"),
+            HashMap::from([
+                ("chickens", "eggs"),
+                ("grain", "chickens"),
+                ("soil", "grain"),
+                ("water", "chickens"),
+            ])
+        );
+        assert!(graph.dominators("soil").is_empty());
+        assert!(graph.dominators("plate").is_empty());
+    }
+
+    #[test]
+    fn diamond_edges_in_cake_graph() {
+        let mut graph = cake_graph();
+        graph.depend_on("cake", "frosting").unwrap();
+        graph.depend_on("frosting", "sugar").unwrap();
+        let diamond = graph.diamond_edges();
+        assert_eq!(diamond.len(), 8);
+        for edge in [
+            ("cake", "eggs"),
+            ("eggs", "chickens"),
+            ("chickens", "grain"),
+            ("cake", "flour"),
+            ("flour", "grain"),
+        ] {
+            assert!(diamond.contains(&edge));
+        }
+        assert!(!diamond.contains(&("cake", "frosting")));
+        assert!(!diamond.contains(&("frosting", "sugar")));
+    }
+
+    #[test]
+    fn stable_labeling_is_reproducible() {
+        let graph = cake_graph();
+        let labels = graph.stable_labeling();
+        assert_eq!(labels, cake_graph().stable_labeling());
+        assert_eq!(labels.len(), 7);
+        let indices: HashSet<usize> = labels.values().copied().collect();
+        assert_eq!(indices, (0..7).collect());
+        assert_eq!(labels["cake"], 0);
+        assert_eq!(labels["water"], 6);
+    }
+
+    #[test]
+    fn is_independent_set_in_cake_graph() {
+        let graph = cake_graph();
+        assert!(graph.is_independent_set(&HashSet::from(["flour", "chickens"])));
+        assert!(graph.is_independent_set(&HashSet::from(["cake", "grain", "plate"])));
+        assert!(graph.is_independent_set(&HashSet::new()));
+        assert!(!graph.is_independent_set(&HashSet::from(["flour", "grain"])));
+        assert!(!graph.is_independent_set(&HashSet::from(["eggs", "water", "chickens"])));
+    }
+
+    #[test]
+    fn grouped_by_dependent_orders_prerequisites_first() {
+        assert_eq!(
+            cake_graph().grouped_by_dependent(),
+            vec![
+                ("grain", vec!["soil", "water"]),
+                ("chickens", vec!["grain", "water"]),
+                ("flour", vec!["grain"]),
+                ("eggs", vec!["chickens"]),
+                ("cake", vec!["eggs", "flour"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn minimal_covering_roots_cover_every_node() {
+        let mut graph = cake_graph();
+        graph.depend_on("bread", "flour").unwrap();
+        graph.depend_on("plate", "cake").unwrap();
+        graph.retain_edges(|from, _| *from != "plate");
+
+        let roots = graph.minimal_covering_roots();
+        assert_eq!(roots, HashSet::from(["cake", "bread", "plate"]));
+        let mut covered = roots.clone();
+        for root in &roots {
+            covered.extend(graph.get_forward_dependencies(root));
+        }
+        assert_eq!(covered, graph.nodes);
+    }
+
+    #[test]
+    fn path_centrality_in_cake_graph() {
+        let centrality = cake_graph().path_centrality();
+        assert_eq!(
+            centrality,
+            HashMap::from([
+                ("cake", 5),
+                ("eggs", 3),
+                ("flour", 2),
+                ("chickens", 3),
+                ("grain", 4),
+                ("soil", 2),
+                ("water", 3),
+            ])
+        );
+        assert!(centrality["grain"] > centrality["soil"]);
+        assert!(centrality["grain"] > centrality["water"]);
+    }
+
+    #[test]
+    fn backward_depths_from_forward_match_layers() {
+        let mut graph = cake_graph();
+        graph.depend_on("frosting", "sugar").unwrap();
+        graph.depend_on("cake", "sugar").unwrap();
+        let forward = graph.forward_depths();
+        let backward = graph.backward_depths_from_forward(&forward);
+        assert_eq!(backward.len(), graph.nodes.len());
+        for (depth, layer) in graph
+            .get_backward_dependency_topological_layers()
+            .into_iter()
+            .enumerate()
+        {
+            for node in layer {
+                assert_eq!(backward[&node], depth);
+            }
+        }
+    }
+
+    #[test]
+    fn near_cyclic_edges_in_cake_graph() {
+        let mut graph = cake_graph();
+        assert!(graph.near_cyclic_edges(1).is_empty());
+        assert_eq!(graph.near_cyclic_edges(2), vec![("chickens", "water")]);
+
+        graph.depend_on("cake", "grain").unwrap();
+        let mut edges = graph.near_cyclic_edges(2);
+        edges.sort();
+        assert_eq!(edges, vec![("cake", "grain"), ("chickens", "water")]);
+        assert_eq!(graph.near_cyclic_edges(3).len(), 2);
+
+        graph.depend_on("cake", "soil").unwrap();
+        assert_eq!(graph.near_cyclic_edges(2).len(), 3);
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let mut graph: AcyclicDependencyGraph<u32> = AcyclicDependencyGraph::new();
+        for i in 0..5_000u32 {
+            graph.depend_on(i, i + 1).unwrap();
+            graph.depend_on(i, i / 2 + 5_001).unwrap();
+        }
+        graph.retain_edges(|from, _| *from != 0);
+        let bytes = graph.to_bytes();
+        let decoded = AcyclicDependencyGraph::<u32>::from_bytes(&bytes).unwrap();
+        assert!(decoded == graph);
+        assert!(decoded.nodes.contains(&0));
+        assert_eq!(decoded.to_bytes(), bytes);
+        assert!(bytes.len() < graph.to_json().len() / 2);
+
+        let empty = AcyclicDependencyGraph::<u8>::new().to_bytes();
+        assert!(AcyclicDependencyGraph::<u8>::from_bytes(&empty)
+            .unwrap()
+            .is_empty());
+
+        let mut sizes: AcyclicDependencyGraph<usize> = AcyclicDependencyGraph::new();
+        sizes.depend_on(usize::MAX, 0).unwrap();
+        sizes.depend_on(0, 1 << 40).unwrap();
+        let bytes = sizes.to_bytes();
+        assert!(AcyclicDependencyGraph::<usize>::from_bytes(&bytes).unwrap() == sizes);
+
+        let mut signed: AcyclicDependencyGraph<i64> = AcyclicDependencyGraph::new();
+        signed.depend_on(3, 2).unwrap();
+        let bytes = signed.to_bytes();
+        assert!(AcyclicDependencyGraph::<i64>::from_bytes(&bytes).unwrap() == signed);
+    }
+
+    #[test]
+    #[should_panic(expected = "node does not fit in a u64 key")]
+    fn binary_rejects_negative_keys() {
+        let mut graph: AcyclicDependencyGraph<i32> = AcyclicDependencyGraph::new();
+        graph.depend_on(-1, 1).unwrap();
+        graph.to_bytes();
+    }
+
+    #[test]
+    fn binary_decode_errors() {
+        let mut graph: AcyclicDependencyGraph<u8> = AcyclicDependencyGraph::new();
+        graph.depend_on(1, 2).unwrap();
+        graph.depend_on(2, 3).unwrap();
+        graph.depend_on(1, 3).unwrap();
+        let bytes = graph.to_bytes();
+        let decode = AcyclicDependencyGraph::<u8>::from_bytes;
+        assert!(matches!(decode(b"JSON{}"), Err(DecodeError::InvalidHeader)));
+        assert!(matches!(
+            decode(&bytes[..bytes.len() - 1]),
+            Err(DecodeError::UnexpectedEnd)
+        ));
+        assert!(matches!(
+            decode(&[&bytes[..], &[0]].concat()),
+            Err(DecodeError::TrailingBytes)
+        ));
+
+        let mut wide = Vec::from(BINARY_MAGIC);
+        wide.extend([BINARY_VERSION, 1, 0x80, 0x02, 0]);
+        assert!(matches!(decode(&wide), Err(DecodeError::InvalidNode)));
+        assert!(AcyclicDependencyGraph::<u16>::from_bytes(&wide).is_ok());
+
+        let mut repeated = Vec::from(BINARY_MAGIC);
+        repeated.extend([BINARY_VERSION, 2, 5, 0, 0]);
+        assert!(matches!(decode(&repeated), Err(DecodeError::InvalidNode)));
+
+        let mut duplicate = Vec::from(BINARY_MAGIC);
+        duplicate.extend([BINARY_VERSION, 2, 1, 1, 2, 0, 1, 0, 1]);
+        assert!(matches!(
+            decode(&duplicate),
+            Err(DecodeError::DuplicateEdge)
+        ));
+
+        let mut looped = Vec::from(BINARY_MAGIC);
+        looped.extend([BINARY_VERSION, 1, 1, 1, 0, 0]);
+        assert!(matches!(
+            decode(&looped),
+            Err(DecodeError::InvalidEdge(DependencyError::SelfReference))
+        ));
+
+        let mut cyclic = Vec::from(BINARY_MAGIC);
+        cyclic.extend([BINARY_VERSION, 3, 1, 1, 1, 3, 0, 1, 1, 2, 2, 0]);
+        assert!(matches!(
+            decode(&cyclic),
+            Err(DecodeError::InvalidEdge(
+                DependencyError::CircularDependency
+            ))
+        ));
+    }
+
+    #[test]
+    fn split_components_partition_the_graph() {
+        let mut graph = cake_graph();
+        graph.depend_on("pie", "apples").unwrap();
+        graph.depend_on("apples", "trees").unwrap();
+        graph.depend_on("plate", "cake").unwrap();
+        graph.retain_edges(|from, _| *from != "plate");
+
+        let components = graph.split_components();
+        let mut sizes: Vec<usize> = components.iter().map(|c| c.nodes.len()).collect();
+        sizes.sort();
+        assert_eq!(sizes, vec![1, 3, 7]);
+
+        let mut union = AcyclicDependencyGraph::new();
+        for component in &components {
+            assert!(component.is_weakly_connected());
+            assert!(component.is_subgraph_of(&graph));
+            union.nodes.extend(component.nodes.iter().copied());
+            for (from, to) in component.edges_topologically() {
+                union.depend_on(from, to).unwrap();
+            }
+        }
+        assert!(union == graph);
+    }
+
+    #[test]
+    fn edge_patch_round_trip() {
+        let mut graph = cake_graph();
+        let mut target = cake_graph();
+        target.retain_edges(|from, to| (*from, *to) != ("chickens", "water"));
+        target.depend_on("water", "rain").unwrap();
+        target.depend_on("frosting", "sugar").unwrap();
+
+        let patch = graph.edge_patch_to(&target);
+        assert_eq!(patch.len(), 3);
+        assert_eq!(patch[0], EdgeOp::Remove("chickens", "water"));
+        graph.apply_edge_patch(&patch).unwrap();
+        assert!(graph == target);
+        assert!(graph.edge_patch_to(&target).is_empty());
+
+        let result =
+            graph.apply_edge_patch(&[EdgeOp::Remove("cake", "plate"), EdgeOp::Add("soil", "cake")]);
+        assert!(matches!(
+            result,
+            Err(DependencyError::InvalidEdge { index: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn depend_on_limited_rejects_deep_chains() {
+        let mut graph = AcyclicDependencyGraph::new();
+        graph.depend_on_limited(1, 2, 2).unwrap();
+        graph.depend_on_limited(2, 3, 2).unwrap();
+        graph.depend_on_limited(1, 3, 2).unwrap();
+        graph.depend_on_limited(4, 3, 2).unwrap();
+        graph.depend_on_limited(0, 4, 2).unwrap();
+        assert!(matches!(
+            graph.depend_on_limited(3, 5, 2),
+            Err(DependencyError::DepthExceeded)
+        ));
+        assert!(matches!(
+            graph.depend_on_limited(5, 1, 2),
+            Err(DependencyError::DepthExceeded)
+        ));
+        assert!(matches!(
+            graph.depend_on_limited(3, 1, 2),
+            Err(DependencyError::CircularDependency)
+        ));
+        assert_eq!(graph.get_forward_dependency_topological_layers().len(), 3);
+
+        graph.depend_on_limited(3, 5, 3).unwrap();
+        assert_eq!(graph.get_forward_dependency_topological_layers().len(), 4);
+    }
+
+    #[test]
+    fn not_required_by_in_cake_graph() {
+        let graph = cake_graph();
+        assert_eq!(
+            graph.not_required_by("eggs"),
+            HashSet::from(["cake", "flour"])
+        );
+        assert!(graph.not_required_by("cake").is_empty());
+        assert_eq!(graph.not_required_by("plate"), graph.nodes);
+    }
+
+    #[test]
+    fn node_betweenness_in_cake_graph() {
+        let scores = cake_graph().node_betweenness();
+        assert_eq!(scores.len(), 7);
+        assert_eq!(scores["grain"], 5.5);
+        assert_eq!(scores["chickens"], 3.5);
+        for node in ["cake", "soil", "water"] {
+            assert_eq!(scores[node], 0.0);
+        }
+        let (top, _) = scores
+            .iter()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .unwrap();
+        assert_eq!(*top, "grain");
+    }
+
+    #[test]
+    fn topological_sort_by_prefers_short_names() {
+        let graph = cake_graph();
+        let order = graph.topological_sort_by(|a, b| a.len().cmp(&b.len()).then(a.cmp(b)));
+        assert!(graph.is_valid_topological_order(&order));
+        assert_eq!(
+            order,
+            vec!["soil", "water", "grain", "flour", "chickens", "eggs", "cake"]
+        );
+
+        let order = graph.topological_sort_by(|a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
+        assert!(graph.is_valid_topological_order(&order));
+        assert_eq!(
+            order,
+            vec!["water", "soil", "grain", "chickens", "flour", "eggs", "cake"]
+        );
+    }
+
+    #[test]
+    fn repair_removes_inconsistent_dependencies() {
+        let mut graph = cake_graph();
+        assert_eq!(graph.repair(), 0);
+
+        // corrupt the graph behind the public interface
+        graph.nodes.remove("soil");
+        graph
+            .forward_dependencies
+            .get_mut("cake")
+            .unwrap()
+            .remove("flour");
+        graph
+            .backward_dependencies
+            .entry("milk")
+            .or_default()
+            .insert("cake");
+        graph
+            .forward_dependencies
+            .entry("eggs")
+            .or_default()
+            .insert("sugar");
+        graph
+            .backward_dependencies
+            .entry("sugar")
+            .or_default()
+            .insert("eggs");
+        graph.forward_dependencies.insert("plate", HashSet::new());
+
+        assert_eq!(graph.repair(), 4);
+        assert_eq!(
+            graph.get_forward_dependencies(&"cake"),
+            HashSet::from(["eggs", "chickens", "grain", "water"])
+        );
+        assert_eq!(graph.get_backward_dependencies(&"flour"), HashSet::new());
+        assert!(!graph.forward_dependencies.contains_key("plate"));
+        assert_eq!(graph.repair(), 0);
+    }
+
+    #[test]
+    fn schedule_with_workers_respects_limits() {
+        let mut graph = cake_graph();
+        for dep in ["sugar", "butter", "milk"] {
+            graph.depend_on("frosting", dep).unwrap();
+        }
+        for workers in 1..5 {
+            let slots = graph.schedule_with_workers(workers);
+            let mut slot_of = HashMap::new();
+            for (i, slot) in slots.iter().enumerate() {
+                assert!(!slot.is_empty() && slot.len() <= workers);
+                for node in slot {
+                    slot_of.insert(*node, i);
+                }
+            }
+            assert_eq!(slot_of.len(), graph.nodes.len());
+            for (from, deps) in &graph.forward_dependencies {
+                for to in deps {
+                    assert!(slot_of[to] < slot_of[from]);
+                }
+            }
+        }
+        assert_eq!(graph.schedule_with_workers(1).len(), 11);
+        assert_eq!(graph.schedule_with_workers(2).len(), 6);
+
+        let layers = cake_graph().get_forward_dependency_topological_layers();
+        let slots: Vec<HashSet<&str>> = cake_graph()
+            .schedule_with_workers(usize::MAX)
+            .into_iter()
+            .map(|slot| slot.into_iter().collect())
+            .collect();
+        assert_eq!(slots, layers);
+    }
+
+    #[test]
+    fn alap_layers_delay_short_chains() {
+        let mut graph = cake_graph();
+        graph.depend_on("cake", "sugar").unwrap();
+        let asap = graph.forward_depths();
+        let alap = graph.alap_layers();
+        assert_eq!(
+            alap,
+            HashMap::from([
+                ("cake", 4),
+                ("eggs", 3),
+                ("flour", 3),
+                ("chickens", 2),
+                ("grain", 1),
+                ("soil", 0),
+                ("water", 0),
+                ("sugar", 3),
+            ])
+        );
+        assert_eq!(asap["sugar"], 0);
+        assert_eq!(asap["flour"], 2);
+        for (node, latest) in &alap {
+            assert!(asap[node] <= *latest);
+            for dep in graph.forward_dependencies.get(node).into_iter().flatten() {
+                assert!(alap[dep] < *latest);
+            }
+        }
+    }
+
+    #[test]
+    fn longest_path_edges_form_a_chain() {
+        let graph = cake_graph();
+        let edges = graph.longest_path_edges();
+        assert_eq!(edges.len(), 4);
+
+        // follow the edges from the single start that is not the end of another edge
+        let ends: HashSet<&str> = edges.iter().map(|(_, to)| *to).collect();
+        let starts: Vec<&str> = edges
+            .iter()
+            .map(|(from, _)| *from)
+            .filter(|from| !ends.contains(from))
+            .collect();
+        assert_eq!(starts, vec!["cake"]);
+        let mut current = "cake";
+        for _ in 0..edges.len() {
+            let (_, to) = edges.iter().find(|(from, _)| *from == current).unwrap();
+            assert!(graph.contains_edge(&current, to));
+            current = to;
+        }
+        assert!(graph.is_leaf(&current));
+        assert!(AcyclicDependencyGraph::<&str>::new()
+            .longest_path_edges()
+            .is_empty());
+    }
+
+    #[test]
+    fn depend_on_tree_allows_one_dependency() {
+        let mut graph = AcyclicDependencyGraph::new();
+        graph.depend_on_tree("eggs", "chickens").unwrap();
+        graph.depend_on_tree("flour", "grain").unwrap();
+        graph.depend_on_tree("chickens", "grain").unwrap();
+        graph.depend_on_tree("eggs", "chickens").unwrap();
+        assert!(matches!(
+            graph.depend_on_tree("eggs", "flour"),
+            Err(DependencyError::NotATree)
+        ));
+        assert!(matches!(
+            graph.depend_on_tree("grain", "eggs"),
+            Err(DependencyError::CircularDependency)
+        ));
+        assert!(matches!(
+            graph.depend_on_tree("grain", "grain"),
+            Err(DependencyError::SelfReference)
+        ));
+        assert_eq!(
+            graph.get_forward_dependencies(&"eggs"),
+            HashSet::from(["chickens", "grain"])
+        );
+        assert_eq!(graph.duplicate_edge_insert_count(), 1);
+    }
+
+    #[test]
+    fn remove_edge_tracked_reports_lost_dependencies() {
+        let mut graph = cake_graph();
+        let before = graph.get_forward_dependencies(&"eggs");
+        let lost = graph.remove_edge_tracked("eggs", "chickens");
+        let after = graph.get_forward_dependencies(&"eggs");
+        assert_eq!(lost, before.difference(&after).copied().collect());
+        assert_eq!(lost, HashSet::from(["chickens", "grain", "soil", "water"]));
+        assert!(graph.nodes.contains("chickens"));
+
+        assert!(graph.remove_edge_tracked("chickens", "water").is_empty());
+        assert!(graph
+            .get_forward_dependencies(&"chickens")
+            .contains("water"));
+        assert!(graph.remove_edge_tracked("cake", "chickens").is_empty());
+        assert_eq!(
+            graph.remove_edge_tracked("flour", "grain"),
+            HashSet::from(["grain", "soil", "water"])
+        );
+    }
+
+    #[test]
+    fn passthrough_nodes_in_cake_graph() {
+        let mut graph = cake_graph();
+        assert_eq!(graph.passthrough_nodes(), HashSet::from(["eggs", "flour"]));
+
+        graph.depend_on("frosting", "sugar").unwrap();
+        graph.depend_on("sugar", "cane").unwrap();
+        graph.depend_on("cake", "frosting").unwrap();
+        assert_eq!(
+            graph.passthrough_nodes(),
+            HashSet::from(["eggs", "flour", "frosting", "sugar"])
+        );
+    }
+
+    #[test]
+    fn out_degree_histogram_of_cake_graph() {
+        assert_eq!(
+            cake_graph().out_degree_histogram(),
+            BTreeMap::from([(0, 2), (1, 2), (2, 3)])
         );
+        assert!(AcyclicDependencyGraph::<&str>::new()
+            .out_degree_histogram()
+            .is_empty());
+    }
+
+    #[test]
+    fn has_exactly_edges_of_cake_graph() {
+        let mut edges = vec![
+            ("cake", "eggs"),
+            ("cake", "flour"),
+            ("eggs", "chickens"),
+            ("flour", "grain"),
+            ("chickens", "grain"),
+            ("grain", "soil"),
+            ("grain", "water"),
+            ("chickens", "water"),
+        ];
+        let graph = cake_graph();
+        assert!(graph.has_exactly_edges(&edges));
+        edges.reverse();
+        edges.push(("cake", "eggs"));
+        assert!(graph.has_exactly_edges(&edges));
+        edges.pop();
+        edges.pop();
+        assert!(!graph.has_exactly_edges(&edges));
+        edges.push(("cake", "soil"));
+        assert!(!graph.has_exactly_edges(&edges));
+    }
+
+    #[test]
+    fn impact_score_weights_depth_and_dependents() {
+        let graph = cake_graph();
+        assert_eq!(graph.impact_score("grain", 1.0, 0.0), 1.0);
+        assert_eq!(graph.impact_score("grain", 0.0, 1.0), 4.0);
+        assert_eq!(graph.impact_score("grain", 2.0, 0.5), 4.0);
+        assert_eq!(graph.impact_score("cake", 1.0, 1.0), 4.0);
+        assert_eq!(graph.impact_score("soil", 1.0, 1.0), 5.0);
+        assert!(graph.impact_score("cake", 1.0, 0.0) > graph.impact_score("soil", 1.0, 0.0));
+        assert!(graph.impact_score("cake", 0.0, 1.0) < graph.impact_score("soil", 0.0, 1.0));
+        assert_eq!(graph.impact_score("plate", 1.0, 1.0), 0.0);
+    }
+
+    #[test]
+    fn build_from_lines_reports_offending_line() {
+        let manifest = "cake: eggs\ncake: flour\n\n# eggs come from chickens\neggs: chickens\nchickens: cake\nflour: grain";
+        let lines = manifest.lines().enumerate().filter_map(|(i, line)| {
+            let (from, to) = line.split_once(": ")?;
+            return Some((i + 1, from, to));
+        });
+        assert!(matches!(
+            AcyclicDependencyGraph::build_from_lines(lines),
+            Err((6, DependencyError::CircularDependency))
+        ));
+
+        let graph = AcyclicDependencyGraph::build_from_lines([
+            (1, "cake", "eggs"),
+            (3, "eggs", "chickens"),
+        ])
+        .unwrap();
+        assert!(graph.depends_on(&"cake", &"chickens"));
+        assert!(matches!(
+            AcyclicDependencyGraph::build_from_lines([(7, "eggs", "eggs")]),
+            Err((7, DependencyError::SelfReference))
+        ));
     }
 }